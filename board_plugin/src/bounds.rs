@@ -1,4 +1,5 @@
-use bevy::prelude::Vec2;
+use bevy::math::Affine3A;
+use bevy::prelude::{Rect, Vec2};
 
 #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::prelude::InspectorOptions))]
 #[cfg_attr(feature = "debug", derive(bevy::reflect::Reflect))]
@@ -14,7 +15,17 @@ impl Bounds2 {
         let top_right = self.position + self.size;
         let x = coordinates.x;
         let y = coordinates.y;
-        return (x >= bottom_left.x && x <= top_right.x) &&
-               (y >= bottom_left.y && y <= top_right.y);
+        (x >= bottom_left.x && x <= top_right.x) &&
+               (y >= bottom_left.y && y <= top_right.y)
     }
-}
\ No newline at end of file
+}
+
+/// Smallest rectangle containing `rect` transformed by `affine`, in the xy plane
+pub(crate) fn transform_rect(affine: &Affine3A, rect: Rect) -> Rect {
+    [rect.min, Vec2::new(rect.min.x, rect.max.y), rect.max, Vec2::new(rect.max.x, rect.min.y)]
+        .map(|corner| affine.transform_point3(corner.extend(0.0)).truncate())
+        .into_iter()
+        .fold(Rect { min: Vec2::INFINITY, max: Vec2::NEG_INFINITY }, |bounds, point| {
+            bounds.union_point(point)
+        })
+}
@@ -1,4 +1,6 @@
-use bevy::prelude::{Component, Reflect};
+use bevy::prelude::Component;
+#[cfg(feature = "debug")]
+use bevy::prelude::Reflect;
 
 #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::prelude::InspectorOptions))]
 #[cfg_attr(feature = "debug", derive(Reflect))]
@@ -4,5 +4,5 @@ use bevy::prelude::Component;
 #[derive(Debug,Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
 pub struct BombNeighbor {
     /// Number of neighbor tiles with bomb
-    pub count : u8
+    pub count : u16
 }
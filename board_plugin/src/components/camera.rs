@@ -0,0 +1,31 @@
+use bevy::prelude::Component;
+#[cfg(feature = "debug")]
+use bevy::reflect::Reflect;
+
+/// Camera panned by dragging with the `InputAction::Pan` inputs and zoomed with the mouse
+/// wheel or a touchpad pinch. Its view is kept on the board
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::prelude::InspectorOptions))]
+#[cfg_attr(feature = "debug", derive(Reflect))]
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct BoardCamera {
+    /// Zoom change of a mouse wheel line, from 0 to 1
+    pub zoom_speed: f32,
+    /// Tiles across the smallest side of the viewport at the closest zoom
+    pub min_visible_tiles: f32,
+    /// Tiles across the largest side of the viewport at the farthest zoom. Bounds the
+    /// chunks spawned for a huge board, smaller boards are seen whole
+    pub max_visible_tiles: f32,
+    /// Distance in logical pixels the mouse moves before a pressed button drags the board
+    pub drag_threshold: f32,
+}
+
+impl Default for BoardCamera {
+    fn default() -> Self {
+        Self {
+            zoom_speed: 0.1,
+            min_visible_tiles: 5.0,
+            max_visible_tiles: 128.0,
+            drag_threshold: 5.0,
+        }
+    }
+}
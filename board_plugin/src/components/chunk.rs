@@ -0,0 +1,13 @@
+use bevy::prelude::Component;
+#[cfg(feature = "debug")]
+use bevy::reflect::Reflect;
+
+/// Square group of tiles spawned and despawned together, see `Board::visible_chunks`.
+/// `x` and `y` are the chunk column and row, not tile coordinates
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::prelude::InspectorOptions))]
+#[cfg_attr(feature = "debug", derive(Reflect))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+pub struct Chunk {
+    pub x: u16,
+    pub y: u16,
+}
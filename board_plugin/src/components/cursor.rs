@@ -0,0 +1,9 @@
+use bevy::prelude::Component;
+#[cfg(feature = "debug")]
+use bevy::reflect::Reflect;
+
+/// Highlight of the tile selected by the keyboard cursor
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::prelude::InspectorOptions))]
+#[cfg_attr(feature = "debug", derive(Reflect))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+pub struct CursorHighlight;
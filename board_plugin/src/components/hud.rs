@@ -0,0 +1,21 @@
+use bevy::prelude::Component;
+#[cfg(feature = "debug")]
+use bevy::reflect::Reflect;
+
+/// Text with the game time
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::prelude::InspectorOptions))]
+#[cfg_attr(feature = "debug", derive(Reflect))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+pub struct HudTimer;
+
+/// Text with the count of bombs which are not flagged yet
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::prelude::InspectorOptions))]
+#[cfg_attr(feature = "debug", derive(Reflect))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+pub struct HudBombCounter;
+
+/// Screen space node holding the HUD texts
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::prelude::InspectorOptions))]
+#[cfg_attr(feature = "debug", derive(Reflect))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+pub struct HudRoot;
@@ -1,13 +1,22 @@
-pub use coordinates::Coordinates;
+pub use minesweeper_core::Coordinates;
 pub use bomb::Bomb;
 pub use bomb_neighbor::BombNeighbor;
+pub use camera::BoardCamera;
+pub use chunk::Chunk;
 
 pub(crate) use pause_cover::PauseCover;
-
-mod coordinates;
+pub(crate) use end_cover::EndCover;
+pub(crate) use cursor::CursorHighlight;
+pub(crate) use hud::{HudBombCounter, HudRoot, HudTimer};
 
 pub mod bomb;
 pub mod bomb_neighbor;
+pub mod camera;
+pub mod chunk;
 pub mod uncover;
 pub mod pause_cover;
+pub mod end_cover;
+pub mod cursor;
+pub mod hud;
+pub mod reveal_wave;
 
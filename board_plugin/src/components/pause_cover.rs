@@ -1,4 +1,6 @@
-use bevy::{prelude::Component, reflect::Reflect};
+use bevy::prelude::Component;
+#[cfg(feature = "debug")]
+use bevy::reflect::Reflect;
 
 #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::prelude::InspectorOptions))]
 #[cfg_attr(feature = "debug", derive(Reflect))]
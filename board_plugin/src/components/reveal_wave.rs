@@ -0,0 +1,9 @@
+use bevy::prelude::Component;
+use bevy::time::Timer;
+
+/// Cover of an already uncovered tile, despawned when the timer finishes
+/// to play the flood fill as a wave
+#[derive(Debug, Clone, Component)]
+pub struct RevealWave {
+    pub timer: Timer,
+}
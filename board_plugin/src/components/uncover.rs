@@ -1,5 +1,9 @@
-use bevy::{prelude::Component, reflect::Reflect};
+use bevy::prelude::Component;
+#[cfg(feature = "debug")]
+use bevy::reflect::Reflect;
 
+/// Request to uncover the tile at the `Coordinates` of the entity, which is despawned
+/// once the tile is uncovered
 #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::prelude::InspectorOptions))]
 #[cfg_attr(feature = "debug", derive(Reflect))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
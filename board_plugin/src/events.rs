@@ -1,21 +1,96 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::ecs::entity::Entity;
 use bevy::ecs::event::Event;
+use minesweeper_core::{GenerationError, Tile};
 
 use crate::components::Coordinates;
+use crate::resources::board::ClickCounts;
 
 // adopted 0.10 to 0.11 
 // https://bevyengine.org/learn/migration-guides/0.10-0.11/#require-derive-event-on-all-events
+/// Request to uncover a tile
 #[derive(Debug, Clone, Copy, Event)]
 pub struct TileTriggerEvent{ 
     pub coordinates: Coordinates
 }
 
+/// Uncovers the neighbours of a counter when all its bombs are flagged
+#[derive(Debug, Clone, Copy, Event)]
+pub struct TileChordEvent{
+    pub coordinates: Coordinates
+}
+
+/// Request to set or remove a flag
 #[derive(Debug, Clone, Copy, Event)]
 pub struct TileMarkEvent{
     pub coordinates : Coordinates
 }
 
+/// All safe tiles are uncovered
+#[derive(Debug, Clone, Copy, Event)]
+pub struct BoardCompletedEvent {
+    /// Time since the first reveal of the player, pauses are excluded
+    pub elapsed: Duration,
+    pub clicks: ClickCounts,
+}
+
+/// A bomb is uncovered
+#[derive(Debug, Clone, Copy, Event)]
+pub struct BombExplosionEvent {
+    pub coordinates: Coordinates
+}
+
+/// A tile is uncovered
+#[derive(Debug, Clone, Copy, Event)]
+pub struct TileRevealedEvent {
+    pub coordinates: Coordinates,
+    pub tile: Tile,
+}
+
+#[derive(Debug, Clone, Copy, Event)]
+pub struct FlagPlacedEvent {
+    pub coordinates: Coordinates
+}
+
+#[derive(Debug, Clone, Copy, Event)]
+pub struct FlagRemovedEvent {
+    pub coordinates: Coordinates
+}
+
+/// No layout solvable without guessing was found in the `GenerationMode::NoGuess` budget.
+/// The board is played with the last tried layout, which needs guessing
+#[derive(Debug, Clone, Copy, Event)]
+pub struct NoGuessFailedEvent {
+    /// First uncovered tile
+    pub start: Coordinates,
+    pub error: GenerationError,
+}
+
+/// A new board is spawned
 #[derive(Debug, Clone, Copy, Event)]
-pub struct BoardCompletedEvent;
+pub struct BoardCreatedEvent {
+    /// Board root entity
+    pub entity: Entity,
+    pub seed: u64,
+}
 
+/// The board is despawned, a new board may follow
 #[derive(Debug, Clone, Copy, Event)]
-pub struct BombExplosionEvent;
\ No newline at end of file
+pub struct BoardDestroyedEvent {
+    /// Board root entity
+    pub entity: Entity,
+}
+
+/// Request to write the current game to a file
+#[derive(Debug, Clone, Event)]
+pub struct SaveGameEvent {
+    pub path: PathBuf,
+}
+
+/// Request to replace the current game with a saved one
+#[derive(Debug, Clone, Event)]
+pub struct LoadGameEvent {
+    pub path: PathBuf,
+}
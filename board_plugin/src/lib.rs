@@ -1,23 +1,34 @@
 pub mod components;
 pub mod resources;
 
+pub use minesweeper_core;
+pub use systems::input::ActionInput;
+
 mod bounds;
-pub(crate) mod events;
+pub mod events;
 mod systems;
+mod tile_look;
+mod tile_shape;
 
-use std::collections::{HashMap, HashSet};
 
 use crate::components::uncover::Uncover;
-use crate::components::{Coordinates, PauseCover};
+use crate::components::{Coordinates, EndCover, PauseCover};
+use bevy::asset::LoadState;
 use bevy::log;
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
-use events::{TileTriggerEvent, TileMarkEvent,BombExplosionEvent, BoardCompletedEvent};
-use resources::tile_map::TileMap;
-use resources::{tile::Tile, BoardOptions};
+use bevy::window::{PrimaryWindow, WindowResized};
+use events::*;
+use minesweeper_core::{Game, Tile};
+use std::collections::HashMap;
+use resources::{BoardOptions, BoardShape, BoardTheme, GenerationMode, InputAction, InputBindings, ShapeImage};
+use systems::save::{CurrentOptions, LoadedOptions};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use bevy::math::Vec3Swizzles;
 use bounds::Bounds2;
-use resources::board::Board;
+use resources::board::{Board, BoardStats};
 use resources::BoardAssets;
 
 /// White box
@@ -30,46 +41,125 @@ pub(crate) const TILE_BOMB_COUNTS: f32 = 2.0;
 pub(crate) const TILE_COVER_Z: f32 = 3.0;
 /// Flag for marked tiles
 pub(crate) const TILE_FLAG_Z: f32 = 4.0;
-/// Pause box
-pub(crate) const PAUSE_COVER_Z: f32 = 100.0;
+/// Keyboard cursor highlight
+pub(crate) const TILE_CURSOR_Z: f32 = 5.0;
+/// Pause box and game result box, UI nodes above the HUD
+pub(crate) const PAUSE_COVER_Z: i32 = 1;
 
 // adopted 0.9 to 0.10, https://bevyengine.org/learn/migration-guides/0.9-0.10/#states
+/// Minesweeper board played in `game_state`.
+///
+/// The plugin requires `T: Serialize + DeserializeOwned` on top of `States`: the states are
+/// part of the `BoardOptions` written in save files, see `SavedGame`. A state enum only needs
+/// `#[derive(Serialize, Deserialize)]`
+///
+/// `BoardAssets` is built from the `ActiveTheme` once it's loaded, or inserted by the
+/// application. The game state must be entered once `BoardAssets` exists
 pub struct BoardPlugin<T>
 where
     T: States,
 {
     pub game_state: T,
     pub pause_state: T,
+    /// State entered when the game is won or lost, input is ignored until restart
+    pub end_state: T,
 }
 
 // struct PauseCover {
 //     pub(crate) entity: Entity,
 // }
 
-impl<T: States> Plugin for BoardPlugin<T> {
+impl<T: States + Serialize + DeserializeOwned> Plugin for BoardPlugin<T> {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(self.game_state.clone()), Self::create_board)
             .add_systems(OnExit(self.game_state.clone()), Self::on_exit_log)
             .add_systems(
                 Update,
                 (
-                    systems::input::input_handling,
+                    systems::input::input_handling.before(Self::generate_on_first_trigger),
+                    systems::cursor::move_cursor::<T>,
+                    systems::cursor::cursor_actions
+                        .before(Self::generate_on_first_trigger)
+                        .after(systems::cursor::move_cursor::<T>),
+                    systems::cursor::show_hint,
+                    Self::generate_on_first_trigger
+                        .before(systems::uncover::trigger_event_handler),
                     systems::uncover::trigger_event_handler,
+                    systems::uncover::chord_event_handler,
                     systems::uncover::uncover_tiles,
                     systems::mark::mark_tiles,
                     Self::recreate_board,
                     Self::pause,
+                    Self::end_game.after(systems::uncover::uncover_tiles),
+                    systems::stats::track_time,
                 )
-                    .run_if(in_state(self.game_state.clone())),
+                    .run_if(in_state(self.game_state.clone()).and_then(resource_exists::<Board>())),
+            )
+            .add_systems(
+                Update,
+                // a board shaped by an image is created once the image is loaded
+                Self::create_board.run_if(
+                    in_state(self.game_state.clone())
+                        .and_then(not(resource_exists::<Board>()))
+                        .and_then(resource_exists::<BoardAssets>()),
+                ),
             )
             .add_systems(
                 Update,
                 (Self::unpause).run_if(in_state(self.pause_state.clone())),
             )
+            .add_systems(
+                Update,
+                (Self::restart).run_if(in_state(self.end_state.clone())),
+            )
+            .add_systems(
+                Update,
+                systems::undo::undo_move::<T>
+                    .after(Self::end_game)
+                    .run_if(in_state(self.game_state.clone()).or_else(in_state(self.end_state.clone()))),
+            )
+            .add_systems(
+                Update,
+                (
+                    // BoardAssets is inserted once the active theme is loaded
+                    Self::resize_board.run_if(resource_exists::<BoardAssets>()),
+                    systems::uncover::animate_reveal,
+                    systems::save::save_game::<T>,
+                    systems::save::load_game::<T>.run_if(resource_exists::<BoardAssets>()),
+                    systems::hud::update_hud::<T>,
+                    systems::cursor::update_cursor_highlight,
+                    systems::camera::control_camera.before(systems::input::input_handling),
+                    systems::theme::apply_theme,
+                    systems::theme::restyle_board::<T>,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                // spawned tiles get their global transform in the same frame
+                systems::chunks::update_chunks
+                    .run_if(resource_exists::<BoardAssets>())
+                    .before(bevy::transform::TransformSystem::TransformPropagate),
+            )
+            .add_systems(Startup, tile_shape::setup_tile_shapes)
+            .init_asset::<BoardTheme>()
+            .init_asset_loader::<resources::BoardThemeLoader>()
+            .init_resource::<resources::Hud>()
+            .init_resource::<resources::BoardCursor>()
+            .init_resource::<systems::camera::CameraDrag>()
+            .init_resource::<resources::InputBindings>()
             .add_event::<TileTriggerEvent>()
+            .add_event::<TileChordEvent>()
             .add_event::<TileMarkEvent>()
             .add_event::<BombExplosionEvent>()
-            .add_event::<BoardCompletedEvent>();
+            .add_event::<BoardCompletedEvent>()
+            .add_event::<TileRevealedEvent>()
+            .add_event::<FlagPlacedEvent>()
+            .add_event::<FlagRemovedEvent>()
+            .add_event::<NoGuessFailedEvent>()
+            .add_event::<BoardCreatedEvent>()
+            .add_event::<BoardDestroyedEvent>()
+            .add_event::<SaveGameEvent>()
+            .add_event::<LoadGameEvent>();
 
         log::info!("Loaded Board Plugin");
 
@@ -80,12 +170,19 @@ impl<T: States> Plugin for BoardPlugin<T> {
     }
 }
 
-impl<T: States> BoardPlugin<T> {
+impl<T: States + Serialize + DeserializeOwned> BoardPlugin<T> {
+    /// Creates a board from `BoardOptions`. A board shaped by an image is created once the
+    /// image is loaded
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn create_board(
         mut commands: Commands,
         board_options: Res<BoardOptions<T>>,
         board_option: Option<Res<Board>>,
-        board_assets: Res<BoardAssets>
+        board_assets: Res<BoardAssets>,
+        window_primary_query: Query<&Window, With<PrimaryWindow>>,
+        mut board_created_ewr: EventWriter<BoardCreatedEvent>,
+        asset_server: Res<AssetServer>,
+        images: Res<Assets<Image>>,
     ) {
         // if board already exists, do nothing
         if board_option.is_some() {
@@ -94,26 +191,92 @@ impl<T: States> BoardPlugin<T> {
 
         let options = board_options.clone();
 
-        let tile_size = options.tile_size_px();
+        let shape_image = match &options.shape {
+            BoardShape::Image(path) => {
+                let handle: Handle<Image> = asset_server.load(path.clone());
+                let load_state = asset_server.get_load_state(&handle);
+                commands.insert_resource(ShapeImage(handle.clone()));
+                // a failed image is reported by empty_tile_map
+                if !matches!(load_state, Some(LoadState::Loaded | LoadState::Failed)) {
+                    return;
+                }
+                images.get(&handle)
+            }
+            _ => None,
+        };
+        // the options of a loaded game don't outlive its board
+        commands.remove_resource::<LoadedOptions<T>>();
+
+        let mut tile_map = options.empty_tile_map(shape_image);
+        let tile_size = options.tile_size_px(Self::window_size(&window_primary_query), &tile_map);
+
+        let seed = options.board_seed();
+        log::info!("board seed: {}", seed);
+        let game = match options.generation {
+            GenerationMode::Immediate => {
+                tile_map.set_bombs(options.bomb_count, options.board_rng(seed).as_mut());
+                #[cfg(feature = "debug")]
+                log::info!("{}", tile_map.console_output());
+                Game::new(tile_map)
+            }
+            // bombs are placed by generate_on_first_trigger
+            GenerationMode::FirstClick { .. } | GenerationMode::NoGuess { .. } => {
+                Game::pending(tile_map)
+            }
+        }
+        .with_question_marks(options.question_marks);
 
-        let mut tile_map = TileMap::empty(options.map_size.columns, options.map_size.rows);
+        let board = Self::spawn_board(
+            &mut commands,
+            game,
+            seed,
+            tile_size,
+            &options,
+            board_assets.as_ref(),
+        );
+
+        // a board without bombs has nothing to find
+        if options.safe_start && board.game.is_generated() {
+            let safe_start = board
+                .game
+                .covered_tiles()
+                .filter(|c| board.game.tile_map()[*c] == Tile::Empty)
+                .min_by_key(|c| (c.y, c.x));
+            // the tile may be in a chunk which is not spawned yet
+            if let Some(coordinates) = safe_start {
+                commands.spawn((Name::new("Safe start"), coordinates, Uncover));
+            }
+        }
 
+        board_created_ewr.send(BoardCreatedEvent {
+            entity: board.entity,
+            seed: board.seed,
+        });
+        commands.insert_resource(board);
+    }
+
+    /// Spawns the board entity with its background, HUD and cursor. The tiles are spawned
+    /// later by `update_chunks`
+    fn spawn_board(
+        commands: &mut Commands,
+        game: Game,
+        seed: u64,
+        tile_size: f32,
+        options: &BoardOptions<T>,
+        board_assets: &BoardAssets,
+    ) -> Board {
         // We deduce the size of the complete board
-        let board_size = options.board_size();
+        let (width, height) = game.tile_map().extent();
+        let board_size = Vec2::new(width, height) * tile_size;
 
         log::info!("board_size: {}", board_size);
 
         // We define the board anchor position (bottom left)
-        let board_position = options.board_position_px(BACKGROUND_Z);
+        let board_position = options.board_position_px(board_size, BACKGROUND_Z);
 
-        tile_map.set_bombs(options.bomb_count);
-        #[cfg(feature = "debug")]
-        log::info!("{}", tile_map.console_output());
-
-        let mut covered_tiles =
-            HashMap::with_capacity((tile_map.width() * tile_map.height()).into());
-
-        let mut safe_start: Option<Entity> = None;
+        // the tiles are spawned by chunks, see update_chunks
+        let cover_entities = vec![None; game.tile_map().len()];
+        let tile_padding = board_assets.tile_padding.unwrap_or(options.tile_padding);
 
         //adopted 0.8 to 0.9
         let board_entity = commands
@@ -127,14 +290,16 @@ impl<T: States> BoardPlugin<T> {
                 },
             ))
             .with_children(|parent| {
+                let board_material = board_assets.contrasted(&board_assets.board_material, Color::WHITE);
                 parent
                     .spawn(SpriteBundle {
-                        // one big white box
+                        // one big box
                         sprite: Sprite {
-                            color: Color::WHITE,
+                            color: board_material.color,
                             custom_size: Some(board_size),
                             ..Default::default()
                         },
+                        texture: board_material.texture,
                         transform: Transform::from_xyz(
                             board_size.x / 2.,
                             board_size.y / 2.,
@@ -144,215 +309,346 @@ impl<T: States> BoardPlugin<T> {
                     })
                     .insert(Name::new("Background"));
 
-                Self::spawn_tiles(
-                    parent,
-                    &tile_map,
-                    tile_size,
-                    options.tile_padding,                    
-                    &mut covered_tiles,
-                    &mut safe_start,
-                    board_assets.as_ref()
-                );
+                systems::cursor::spawn_cursor(parent, options.topology, tile_size - tile_padding);
             })
             .id();
 
-        if options.safe_start {
-            if let Some(entity) = safe_start {
-                commands.entity(entity).insert(Uncover);
-            }
-        }
-
-        commands.insert_resource(Board {
-            tile_map: tile_map.clone(),
+        Board {
+            game,
             bounds: Bounds2 {
                 position: board_position.xy(),
                 size: board_size,
             },
             tile_size,
-            covered_tiles,
-            marked_tiles: HashSet::new(),
+            tile_padding,
+            cover_entities,
+            chunks: HashMap::new(),
             entity: board_entity,
+            stats: BoardStats::default(),
+            click_history: Vec::new(),
+            reveal_animation: options.reveal_animation,
+            seed,
+        }
+    }
+
+    /// Size of the primary window, zero if there is no window
+    fn window_size(window_primary_query: &Query<&Window, With<PrimaryWindow>>) -> Vec2 {
+        match window_primary_query.get_single() {
+            Ok(window) => Vec2::new(window.width(), window.height()),
+            Err(_) => Vec2::ZERO,
+        }
+    }
+
+    /// Rebuilds the board entities with the new tile size when the window is resized
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
+    fn resize_board(
+        mut commands: Commands,
+        mut window_resized_evr: EventReader<WindowResized>,
+        window_primary_query: Query<&Window, With<PrimaryWindow>>,
+        board: Option<ResMut<Board>>,
+        board_options: CurrentOptions<T>,
+        board_assets: Res<BoardAssets>,
+        mut board_destroyed_ewr: EventWriter<BoardDestroyedEvent>,
+        mut board_created_ewr: EventWriter<BoardCreatedEvent>,
+    ) {
+        if window_resized_evr.is_empty() {
+            return;
+        }
+        window_resized_evr.clear();
+
+        let Some(mut board) = board else {
+            return;
+        };
+        let tile_size = board_options.tile_size_px(
+            Self::window_size(&window_primary_query),
+            board.game.tile_map(),
+        );
+        if tile_size == board.tile_size {
+            return;
+        }
+
+        log::info!("Resize board, tile size {} -> {}", board.tile_size, tile_size);
+        let old_entity = board.entity;
+        Self::respawn_board(&mut commands, &mut board, tile_size, &board_options, &board_assets);
+        board_destroyed_ewr.send(BoardDestroyedEvent { entity: old_entity });
+        board_created_ewr.send(BoardCreatedEvent {
+            entity: board.entity,
+            seed: board.seed,
         });
     }
 
-    fn spawn_tiles(
-        parent: &mut ChildBuilder,
-        tile_map: &TileMap,
+    /// Replaces the board entities with new ones from its game, the stats are kept.
+    /// The caller sends the `BoardDestroyedEvent` and `BoardCreatedEvent`
+    fn respawn_board(
+        commands: &mut Commands,
+        board: &mut Board,
         tile_size: f32,
-        tile_padding: f32,
-        covered_tiles: &mut HashMap<Coordinates, Entity>,
-        safe_start_entity: &mut Option<Entity>,
+        options: &BoardOptions<T>,
         board_assets: &BoardAssets,
     ) {
-        // remove duplicate of logic from original tutorial
-        let tile_real_size = tile_size - tile_padding;
-        let sprites_size = Some(Vec2::splat(tile_real_size));
-
-        for (y, line) in tile_map.iter().enumerate() {
-            for (x, tile) in line.iter().enumerate() {
-                let coordinates = Coordinates {
-                    x: x as u16,
-                    y: y as u16,
-                };
-
-                log::info!("Spawn tile {:?} at {:?}", tile, coordinates);
-
-                let mut commands = parent.spawn(SpriteBundle {                    
-                    sprite: Sprite {
-                        color: board_assets.tile_material.color,
-                        custom_size: sprites_size,
-                        ..Default::default()
-                    },
-                    transform: Transform::from_xyz(
-                        (x as f32 * tile_size) + (tile_size / 2.),
-                        (y as f32 * tile_size) + (tile_size / 2.),
-                        TILE_Z,
-                    ),
-                    ..Default::default()
-                });
-
-                commands
-                    .insert(Name::new(format!("Tile: ({}, {})", x, y)))
-                    .insert(coordinates);
-
-                commands.with_children(|parent| {
-                    let entity = parent
-                        .spawn(SpriteBundle {
-                            sprite: Sprite {
-                                custom_size: sprites_size,
-                                color: board_assets.covered_tile_material.color,
-                                ..Default::default()
-                            },
-                            transform: Transform::from_xyz(0.0, 0.0, TILE_COVER_Z),
-                            ..Default::default()
-                        })
-                        .insert(Name::new("Tile Cover"))
-                        .id();
-                    covered_tiles.insert(coordinates, entity);
-                    if safe_start_entity.is_none() && *tile == Tile::Empty {
-                        *safe_start_entity = Some(entity);
-                    }
-                });
-
-                match tile {
-                    Tile::Bomb => {
-                        commands.insert(components::Bomb);
-                        commands.with_children(|parent| {
-                            parent.spawn(SpriteBundle {
-                                sprite: Sprite {
-                                    custom_size: sprites_size,
-                                    ..Default::default()
-                                },
-                                transform: Transform::from_xyz(0., 0., TILE_BOMB_COUNTS),
-                                texture: board_assets.bomb_material.texture.clone(),
-                                ..Default::default()
-                            });
-                        });
-                    }
-                    Tile::BombNeighbour(bombs_count) => {
-                        commands.insert(components::BombNeighbor {
-                            count: *bombs_count,
-                        });
-                        commands.with_children(|parent| {
-                            parent.spawn(Self::bomb_count_text_bundle(
-                                *bombs_count,                                
-                                tile_real_size,
-                                board_assets
-                            ));
-                        });
-                    }
-                    Tile::Empty => (),
-                }
-            }
-        }
+        commands.entity(board.entity).despawn_recursive();
+        let game = std::mem::take(&mut board.game);
+        let (seed, stats) = (board.seed, board.stats);
+        *board = Self::spawn_board(commands, game, seed, tile_size, options, board_assets);
+        board.stats = stats;
     }
 
-    fn bomb_count_text_bundle(count: u8, font_size: f32, board_assets: &BoardAssets) -> Text2dBundle {
-        let color = board_assets.bomb_counter_color(count);
+    /// Places the bombs around the first triggered tile of a not yet generated board
+    /// and spawns the bombs and counters sprites.
+    fn generate_on_first_trigger(
+        mut commands: Commands,
+        mut board: ResMut<Board>,
+        board_options: CurrentOptions<T>,
+        board_assets: Res<BoardAssets>,
+        mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+        mut no_guess_failed_ewr: EventWriter<NoGuessFailedEvent>,
+        tiles: Query<(Entity, &Coordinates)>,
+    ) {
+        if board.game.is_generated() {
+            tile_trigger_evr.clear();
+            return;
+        }
 
-        let style = TextStyle {
-            font: board_assets.bomb_counter_font.clone(),
-            font_size: font_size,
-            color,
+        let Some(start) = tile_trigger_evr.read().next().map(|e| e.coordinates) else {
+            return;
         };
-        // adopted 0.9 to 0.10 and simplified API
-        let text =
-            Text::from_section(count.to_string(), style).with_alignment(TextAlignment::Center);
-
-        Text2dBundle {
-            text,
-            // z-order, print text on top of the tile
-            transform: Transform::from_xyz(0.0, 0.0, TILE_BOMB_COUNTS),
-            ..Default::default()
+        tile_trigger_evr.clear();
+
+        // a void cell can't be the start of a no-guess board
+        if !board.game.tile_map().is_tile(start) {
+            return;
+        }
+
+        let mut rng = board_options.board_rng(board.seed);
+        board.game.generate(|tile_map| match board_options.generation {
+            GenerationMode::NoGuess {
+                max_attempts,
+                max_duration,
+            } => {
+                match tile_map.set_bombs_no_guess(
+                    board_options.bomb_count,
+                    rng.as_mut(),
+                    start,
+                    max_attempts,
+                    max_duration,
+                ) {
+                    Ok(attempts) => log::info!("No-guess board found in {} attempts", attempts),
+                    Err(error) => {
+                        log::warn!("Failed to generate a no-guess board: {}", error);
+                        no_guess_failed_ewr.send(NoGuessFailedEvent { start, error });
+                    }
+                }
+            }
+            _ => {
+                let mut safe_tiles = vec![start];
+                if let GenerationMode::FirstClick {
+                    safe_neighbourhood: true,
+                } = board_options.generation
+                {
+                    safe_tiles.extend(tile_map.safe_square_at(start));
+                }
+                tile_map.set_bombs_excluding(board_options.bomb_count, rng.as_mut(), &safe_tiles);
+            }
+        });
+        log::info!("Bombs are placed around {}", start);
+        #[cfg(feature = "debug")]
+        log::info!("{}", board.game.tile_map().console_output());
+
+        let tile_real_size = board.sprite_size() - board.tile_padding;
+        for (entity, coordinates) in tiles.iter() {
+            let tile = board.game.tile_map()[*coordinates];
+            systems::chunks::spawn_tile_content(
+                &mut commands.entity(entity),
+                &tile,
+                false,
+                tile_real_size,
+                &board_assets,
+            );
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn recreate_board(
         mut commands: Commands,
-        keys: Res<Input<KeyCode>>,
+        input: ActionInput,
         board: Res<Board>,
         board_assets: Res<BoardAssets>,
         board_options: Res<BoardOptions<T>>,
+        window_primary_query: Query<&Window, With<PrimaryWindow>>,
+        mut board_destroyed_ewr: EventWriter<BoardDestroyedEvent>,
+        board_created_ewr: EventWriter<BoardCreatedEvent>,
+        asset_server: Res<AssetServer>,
+        images: Res<Assets<Image>>,
     ) {
-        if keys.just_released(KeyCode::G) {
-            log::info!("G is released");
+        if input.just_released(InputAction::Regenerate) {
+            log::info!("Regenerate is released");
             commands.entity(board.entity).despawn_recursive();
-            BoardPlugin::create_board(commands, board_options, None, board_assets)
+            board_destroyed_ewr.send(BoardDestroyedEvent {
+                entity: board.entity,
+            });
+            BoardPlugin::create_board(
+                commands,
+                board_options,
+                None,
+                board_assets,
+                window_primary_query,
+                board_created_ewr,
+                asset_server,
+                images,
+            )
         }
     }
 
     fn pause(
         mut commands: Commands,
-        keys: Res<Input<KeyCode>>,
+        input: ActionInput,
         mut next_state: ResMut<NextState<T>>,
         board_options: Res<BoardOptions<T>>,
         board_assets: Res<BoardAssets>,
+        board: Res<Board>,
     ) {
-        if keys.just_released(KeyCode::P) {
+        if input.just_released(InputAction::Pause) {
             next_state.set(board_options.pause_state.clone());
 
-            let font: Handle<Font> = board_assets.menu_font.clone();
-            let text_style = TextStyle {
-                font: font,
-                font_size: board_options.tile_size_px(),
-                color: Color::YELLOW,
+            let message = match input.bindings().describe(InputAction::Pause) {
+                Some(inputs) => format!("Paused! Press {} to continue", inputs),
+                None => "Paused!".to_string(),
             };
-            let text = Text::from_section("Paused! Press P to continue", text_style)
-                .with_alignment(TextAlignment::Center);
+            Self::spawn_cover(
+                &mut commands,
+                &message,
+                board_assets.pause_cover_color,
+                &board,
+                &board_assets,
+            )
+            .insert(Name::new("Pause cover"))
+            .insert(PauseCover);
+        }
+    }
+
+    /// Box over the whole window with a centered message, in screen space so that
+    /// the camera doesn't move it
+    fn spawn_cover<'w, 's, 'a>(
+        commands: &'a mut Commands<'w, 's>,
+        message: &str,
+        color: Color,
+        board: &Board,
+        board_assets: &BoardAssets,
+    ) -> EntityCommands<'w, 's, 'a> {
+        let font: Handle<Font> = board_assets.menu_font.clone();
+        let text_style = TextStyle {
+            font,
+            font_size: board.tile_size,
+            color: board_assets.cover_text_color,
+        };
+        let text = Text::from_section(message, text_style)
+            .with_alignment(TextAlignment::Center);
+
+        let mut cover = commands.spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: color.into(),
+            z_index: ZIndex::Global(PAUSE_COVER_Z),
+            ..Default::default()
+        });
+        cover.with_children(|parent| {
+            parent.spawn(TextBundle {
+                text,
+                ..Default::default()
+            });
+        });
+        cover
+    }
 
+    /// Stops the game when a bomb explodes or the board is completed.
+    /// On explosion the spawned chunks are drawn again to show the bombs and wrong flags
+    #[allow(clippy::too_many_arguments)]
+    fn end_game(
+        mut commands: Commands,
+        mut next_state: ResMut<NextState<T>>,
+        board_options: Res<BoardOptions<T>>,
+        board_assets: Res<BoardAssets>,
+        mut board: ResMut<Board>,
+        bindings: Res<InputBindings>,
+        mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+        mut board_completed_evr: EventReader<BoardCompletedEvent>,
+    ) {
+        let lost = !bomb_explosion_evr.is_empty();
+        let won = !board_completed_evr.is_empty();
+        bomb_explosion_evr.clear();
+        board_completed_evr.clear();
+        if !lost && !won {
+            return;
+        }
 
-            let board_size = board_options.board_size();
-            commands
-                .spawn(SpriteBundle {
-                    sprite: Sprite {
-                        color: Color::SEA_GREEN,
-                        custom_size: Some(board_size),
-                        ..Default::default()
-                    },
-                    transform: Transform::from_xyz(0.0, 0.0, PAUSE_COVER_Z),
-                    ..Default::default()
-                })
-                .insert(Name::new("Pause cover"))
-                .insert(PauseCover)
-                .with_children(|parent| {
-                    parent.spawn(Text2dBundle{
-                        text,
-                        transform: Transform::from_xyz(0.0, 0.0, PAUSE_COVER_Z+1.0),
-                        ..Default::default()
-                    });
-        
-                });
+        if lost {
+            board.despawn_chunks(&mut commands);
+        }
+        log::info!("Game is over, won: {}", !lost);
+
+        Self::spawn_end_cover(&mut commands, lost, &board, &board_assets, &bindings);
+        next_state.set(board_options.end_state.clone());
+    }
+
+    /// Result box of a won or lost game
+    fn spawn_end_cover(
+        commands: &mut Commands,
+        lost: bool,
+        board: &Board,
+        board_assets: &BoardAssets,
+        bindings: &InputBindings,
+    ) {
+        let mut message = if lost {
+            "Boom!".to_string()
+        } else {
+            "Board completed!".to_string()
+        };
+        if let Some(inputs) = bindings.describe(InputAction::Restart) {
+            message += &format!(" Press {} to restart", inputs);
+        }
+        Self::spawn_cover(commands, &message, board_assets.end_cover_color, board, board_assets)
+            .insert(Name::new("End cover"))
+            .insert(EndCover);
+    }
+
+    /// Starts a new game from the end state
+    fn restart(
+        mut commands: Commands,
+        input: ActionInput,
+        mut next_state: ResMut<NextState<T>>,
+        board_options: Res<BoardOptions<T>>,
+        board: Res<Board>,
+        end_cover_query: Query<Entity, With<EndCover>>,
+        mut board_destroyed_ewr: EventWriter<BoardDestroyedEvent>,
+    ) {
+        if input.just_released(InputAction::Restart) {
+            for entity in end_cover_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            commands.entity(board.entity).despawn_recursive();
+            board_destroyed_ewr.send(BoardDestroyedEvent {
+                entity: board.entity,
+            });
+            // the new board is created on entering the game state
+            commands.remove_resource::<Board>();
+            next_state.set(board_options.game_state.clone());
         }
     }
 
     fn unpause(
         mut commands: Commands,
-        keys: Res<Input<KeyCode>>,
+        input: ActionInput,
         mut next_state: ResMut<NextState<T>>,
         board_options: Res<BoardOptions<T>>,
         pause_cover_query: Query<Entity, With<PauseCover>>,
     ) {
-        if keys.just_released(KeyCode::P) {            
+        if input.just_released(InputAction::Pause) {            
             let x: Entity = pause_cover_query.single();
             commands.entity(x).despawn_recursive();
             next_state.set(board_options.game_state.clone())
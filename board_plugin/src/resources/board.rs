@@ -1,13 +1,37 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::time::Duration;
 
 use crate::bounds::Bounds2;
-use crate::{Coordinates, TileMap};
+use crate::components::Chunk;
+use crate::Coordinates;
 use bevy::ecs::system::Resource;
-use bevy::math::Vec2;
-use bevy::{log, prelude::*};
+use bevy::math::{Rect, Vec2};
+use bevy::prelude::*;
+use minesweeper_core::{FlagChange, Game, RevealedTile};
+use serde::{Deserialize, Serialize};
 
-use bevy::window::Window;
+/// Number of tiles on a side of a chunk
+pub const CHUNK_SIZE: u16 = 32;
 
+/// Number of player actions, the ones which change nothing are not counted
+#[cfg_attr(feature = "debug", derive(Reflect))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClickCounts {
+    pub reveal: u32,
+    pub flag: u32,
+    pub chord: u32,
+}
+
+/// Progress of the player
+#[cfg_attr(feature = "debug", derive(Reflect))]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct BoardStats {
+    /// Time since the first reveal of the player, pauses are excluded
+    pub elapsed: Duration,
+    pub clicks: ClickCounts,
+}
+
+/// Maps the game model to the board entities
 #[cfg_attr(
     feature = "debug",
     derive(bevy_inspector_egui::prelude::InspectorOptions)
@@ -15,99 +39,222 @@ use bevy::window::Window;
 #[cfg_attr(feature = "debug", derive(Reflect))]
 #[derive(Debug, Resource)]
 pub struct Board {
-    pub tile_map: TileMap,
+    /// Rules and state of the game
+    #[cfg_attr(feature = "debug", reflect(ignore))]
+    pub game: Game,
 
     pub bounds: Bounds2,
     pub tile_size: f32,
+    /// Gap between the tile sprites, see `BoardOptions::tile_padding`
+    pub tile_padding: f32,
 
-    #[reflect(ignore)]
-    #[reflect(default = "HashMap::new")]
-    pub covered_tiles: HashMap<Coordinates, Entity>,
+    /// Cover entities of the covered tiles, by index of the tile map.
+    /// Tiles of chunks which are not spawned have no entity
+    #[cfg_attr(feature = "debug", reflect(ignore))]
+    #[cfg_attr(feature = "debug", reflect(default = "Vec::new"))]
+    pub cover_entities: Vec<Option<Entity>>,
 
-    #[reflect(ignore)]
-    #[reflect(default = "HashSet::new")]
-    pub marked_tiles: HashSet<Coordinates>,
+    /// Spawned chunk entities, children of the board entity
+    #[cfg_attr(feature = "debug", reflect(ignore))]
+    #[cfg_attr(feature = "debug", reflect(default = "HashMap::new"))]
+    pub chunks: HashMap<Chunk, Entity>,
 
     pub entity: Entity,
+
+    pub stats: BoardStats,
+
+    /// Click counts before each move of `game` which can be undone, the last one at the end
+    pub click_history: Vec<ClickCounts>,
+
+    /// Delay between the waves of an uncovered empty region, see `BoardOptions`
+    pub reveal_animation: Option<Duration>,
+
+    /// Seed used to generate the bombs layout, can be passed back with `BoardOptions::seed`
+    pub seed: u64,
 }
 
+/// Flag change with the cover entity of the tile, `None` if its chunk is not spawned
 pub(crate) enum ToggleMarkResult {
-    FlagIsSet(Entity),
-    FlagIsUnset(Entity),
+    FlagIsSet(Option<Entity>),
+    FlagIsUnset(Option<Entity>),
+    /// The flag is replaced by a question mark
+    QuestionIsSet(Option<Entity>),
+    QuestionIsUnset(Option<Entity>),
     DidNothing
 }
 
 impl Board {
-    /// Translates a mouse position to board coordinates (column and row of tile)
-    pub(crate) fn mouse_position(&self, window: &Window, position: Vec2) -> Option<Coordinates> {
-        let windows_size = Vec2 {
-            x: window.width(),
-            y: window.height(),
-        };
+    /// Translates a cursor position in the window to board coordinates (column and row
+    /// of tile), through the projection of `camera` and the transform of the board entity.
+    /// Returns `None` out of the tiles shapes, in the padding between them included
+    pub(crate) fn mouse_position(
+        &self,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        board_transform: &GlobalTransform,
+        position: Vec2,
+    ) -> Option<Coordinates> {
+        let viewport_origin = camera.logical_viewport_rect().map_or(Vec2::ZERO, |rect| rect.min);
+        let ray = camera.viewport_to_world(camera_transform, position - viewport_origin)?;
+        let distance = ray.intersect_plane(board_transform.translation(), board_transform.back())?;
+        let position = board_transform
+            .affine()
+            .inverse()
+            .transform_point3(ray.get_point(distance))
+            .truncate();
 
-        let position_at_board = position - (windows_size / 2.0);
-
-        if !self.bounds.in_bounds(position_at_board) {
+        let tile_map = self.game.tile_map();
+        let coordinates = tile_map
+            .topology()
+            .tile_at((position.x / self.tile_size, position.y / self.tile_size))
+            .filter(|c| tile_map.is_tile(*c))?;
+        let apothem = (self.tile_size - self.tile_padding) / 2.0;
+        let offset = position - self.tile_position(coordinates);
+        if !tile_map.topology().is_inside_tile((offset.x, offset.y), apothem) {
             return None;
         }
+        Some(coordinates)
+    }
 
-        let coordinates = position_at_board - self.bounds.position;
-        return Some(Coordinates {
-            x: (coordinates.x / self.tile_size) as u16,
-            // adopted 0.10 to 0.11, the y of click is inverted realtive to the board
-            // https://bevyengine.org/learn/migration-guides/0.10-0.11/#consistent-screen-space-coordinates
-            // max row index is height - 1
-            y: self.tile_map.height() - 1 - (coordinates.y / self.tile_size) as u16,
-        });
+    /// Center of a tile relative to the board entity
+    pub fn tile_position(&self, coordinates: Coordinates) -> Vec2 {
+        let (x, y) = self.game.tile_map().topology().tile_center(coordinates);
+        Vec2::new(x, y) * self.tile_size
     }
 
-    /// Retrivies a covered tile entity
+    /// Side of the square sprites drawn in a tile, see `Topology::sprite_size`
+    pub fn sprite_size(&self) -> f32 {
+        self.tile_size * self.game.tile_map().topology().sprite_size()
+    }
+
+    /// Retrivies a covered and not flagged tile entity
     pub fn tile_to_uncover(&self, coordinates: &Coordinates) -> Option<&Entity> {
-        return self.covered_tiles.get(coordinates);
+        if self.game.is_flagged(*coordinates) {
+            return None;
+        }
+        self
+            .game
+            .tile_map()
+            .index_of(*coordinates)
+            .and_then(|index| self.cover_entities[index].as_ref())
     }
 
-    /// We try to uncover a tile, returning the entity
-    pub fn try_uncover_tile(&mut self, coordinates: &Coordinates) -> Option<Entity> {
-        return if self.marked_tiles.contains(coordinates) {
-            None
-        } else {
-            self.covered_tiles.remove(coordinates)
+    /// Chunks intersecting `view`, a rectangle in pixels relative to the board entity
+    pub fn visible_chunks(&self, view: Rect) -> Vec<Chunk> {
+        let tile_map = self.game.tile_map();
+        let (width, height) = tile_map.extent();
+        let extent = Rect::new(0.0, 0.0, width, height);
+        let view = Rect::from_corners(view.min / self.tile_size, view.max / self.tile_size)
+            .intersect(extent);
+        if view.is_empty() || tile_map.is_empty() {
+            return Vec::new();
         }
+
+        // the nearest tiles of the corners, widened by a tile for the hexagonal offsets
+        let topology = tile_map.topology();
+        let corner = |x: f32, y: f32| topology.tile_at((x, y)).unwrap_or_default();
+        let (min, max) = (corner(view.min.x, view.min.y), corner(view.max.x, view.max.y));
+        let last = (tile_map.width() - 1, tile_map.height() - 1);
+        let (min_x, min_y) = (min.x.saturating_sub(1), min.y.saturating_sub(1));
+        let (max_x, max_y) = (
+            max.x.saturating_add(1).min(last.0),
+            max.y.saturating_add(1).min(last.1),
+        );
+
+        (min_y / CHUNK_SIZE..=max_y / CHUNK_SIZE)
+            .flat_map(|y| (min_x / CHUNK_SIZE..=max_x / CHUNK_SIZE).map(move |x| Chunk { x, y }))
+            .collect()
+    }
+
+    /// Tiles of `chunk`, void cells excluded
+    pub fn chunk_tiles(&self, chunk: Chunk) -> Vec<Coordinates> {
+        let tile_map = self.game.tile_map();
+        let start = (chunk.x as u32 * CHUNK_SIZE as u32, chunk.y as u32 * CHUNK_SIZE as u32);
+        let end = (
+            (start.0 + CHUNK_SIZE as u32).min(tile_map.width() as u32),
+            (start.1 + CHUNK_SIZE as u32).min(tile_map.height() as u32),
+        );
+        (start.1..end.1)
+            .flat_map(|y| (start.0..end.0).map(move |x| Coordinates { x: x as u16, y: y as u16 }))
+            .filter(|c| tile_map.is_tile(*c))
+            .collect()
     }
 
+    /// Cover entity of a covered tile
+    pub fn cover_entity(&self, coordinates: Coordinates) -> Option<Entity> {
+        let index = self.game.tile_map().index_of(coordinates)?;
+        self.cover_entities[index]
+    }
+
+    /// Despawns the spawned chunks, `update_chunks` spawns them again from the game
+    pub(crate) fn despawn_chunks(&mut self, commands: &mut Commands) {
+        for (_, entity) in self.chunks.drain() {
+            commands.entity(entity).despawn_recursive();
+        }
+        self.cover_entities.fill(None);
+    }
+
+    /// Uncovers a tile with its whole empty region at once. Returns the uncovered tiles
+    /// with their cover entities, `None` if their chunk is not spawned. The covers are
+    /// no longer tracked by the board
+    pub fn reveal(&mut self, coordinates: Coordinates) -> Vec<(RevealedTile, Option<Entity>)> {
+        let revealed = self.game.reveal(coordinates);
+        revealed
+            .into_iter()
+            .map(|r| {
+                let cover = self
+                    .game
+                    .tile_map()
+                    .index_of(r.coordinates)
+                    .and_then(|index| self.cover_entities[index].take());
+                (r, cover)
+            })
+            .collect()
+    }
+
+    /// Counts a click of the player which changed the game, undone with its move
+    pub(crate) fn count_click(&mut self, click: impl FnOnce(&mut ClickCounts)) {
+        self.click_history.push(self.stats.clicks);
+        click(&mut self.stats.clicks);
+    }
 
     /// Different from tutorial. use custom enum ToggleMarkResult instead of Option<(Entity,Bool>)
     pub(crate) fn try_toggle_mark(
         &mut self,
         coordinates: &Coordinates,
     ) -> ToggleMarkResult {
-        // can set flag only on covered tiles
-        return match self.covered_tiles.get(coordinates) {
-            Some(entity) => {
-                if self.marked_tiles.contains(coordinates) {
-                    // Different from tutorial. Don't create fn unmark_title
-                    self.marked_tiles.remove(coordinates);
-                    ToggleMarkResult::FlagIsUnset(entity.clone())
-                } else {
-                    self.marked_tiles.insert(*coordinates);
-                    ToggleMarkResult::FlagIsSet(entity.clone())
-                }       
-            }
-            None => ToggleMarkResult::DidNothing,
+        // the game only flags covered tiles, their chunk may not be spawned
+        let entity = self.cover_entity(*coordinates);
+        match self.game.toggle_flag(*coordinates) {
+            FlagChange::Set => ToggleMarkResult::FlagIsSet(entity),
+            FlagChange::Unset => ToggleMarkResult::FlagIsUnset(entity),
+            FlagChange::Question => ToggleMarkResult::QuestionIsSet(entity),
+            FlagChange::Unquestion => ToggleMarkResult::QuestionIsUnset(entity),
+            FlagChange::Nothing => ToggleMarkResult::DidNothing,
         }
     }
 
-    /// We retrieve the adjancent covered tile entities of `coordinates`
-    pub fn adjancent_covered_tiles(&self, coordinate: Coordinates) -> Vec<Entity> {
-        return self
-            .tile_map
-            .safe_square_at(coordinate)
-            .filter_map(|c| self.covered_tiles.get(&c))
-            .copied()
-            .collect();
+    /// Cover entities of the bombs which are not flagged
+    pub fn unflagged_bomb_covers(&self) -> Vec<Entity> {
+        self
+            .game
+            .covered_tiles()
+            .filter(|c| self.game.tile_map().is_bomb_at(*c) && !self.game.is_flagged(*c))
+            .filter_map(|c| self.cover_entity(c))
+            .collect()
+    }
+
+    /// Cover entities of the flagged tiles without bomb
+    pub fn wrong_flag_covers(&self) -> Vec<Entity> {
+        self
+            .game
+            .flagged_tiles()
+            .filter(|c| !self.game.tile_map().is_bomb_at(*c))
+            .filter_map(|c| self.cover_entity(c))
+            .collect()
     }
 
     pub fn is_completed(&self) -> bool {
-        return self.tile_map.bomb_count() as usize == self.covered_tiles.len();
+        self.game.is_completed()
     }
 }
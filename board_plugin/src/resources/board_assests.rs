@@ -1,4 +1,8 @@
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
+use minesweeper_core::Topology;
+
+use crate::resources::{AtlasCells, CounterPalette, TileAtlas};
 
 #[derive(Debug, Clone, Default)]
 pub struct SpriteMaterial {
@@ -22,7 +26,14 @@ impl SpriteMaterial {
     }
 }
 
-#[derive(Resource)]
+/// Components drawing a sprite, a cell of the atlas or a material
+enum SpriteLook {
+    Atlas(TextureAtlasSprite, Handle<TextureAtlas>),
+    Material(Sprite, Handle<Image>),
+}
+
+/// Look of the board, can be loaded from a theme file, see `BoardTheme`
+#[derive(Debug, Clone, Resource)]
 pub struct BoardAssets {
     pub label: String,
 
@@ -41,6 +52,63 @@ pub struct BoardAssets {
     pub bomb_material: SpriteMaterial,
 
     pub menu_font: Handle<Font>,
+
+    /// Box over the board while the game is paused
+    pub pause_cover_color: Color,
+
+    /// Box over the board when the game is won or lost
+    pub end_cover_color: Color,
+
+    /// Message of the pause and end boxes
+    pub cover_text_color: Color,
+
+    /// Question marks drawn as text, when the atlas has no question mark cell
+    pub question_mark_color: Color,
+
+    /// Tint of the covers of the wrong flags of a lost game, without a wrong flag cell
+    /// in the atlas
+    pub wrong_flag_color: Color,
+
+    /// Replaces `BoardOptions::tile_padding`
+    pub tile_padding: Option<f32>,
+
+    /// Draws the tiles, flags, bombs and counters with the cells of an atlas instead
+    /// of the materials and the counter font
+    pub atlas: Option<TileAtlas>,
+
+    /// Replaces `bomb_counter_colors`, e.g. for color vision deficiencies.
+    /// Kept when the theme is switched
+    pub counter_palette: CounterPalette,
+
+    /// Draws a ring of pips around the counters up to 8, one per bomb, so that
+    /// counters don't rely on colors. Kept when the theme is switched
+    pub counter_glyphs: bool,
+}
+
+/// Built-in look with plain colors and the default font, used when no theme can be loaded
+impl Default for BoardAssets {
+    fn default() -> Self {
+        BoardAssets {
+            label: "Built-in".to_string(),
+            board_material: SpriteMaterial::color(Color::WHITE),
+            tile_material: SpriteMaterial::color(Color::DARK_GRAY),
+            covered_tile_material: SpriteMaterial::color(Color::GRAY),
+            bomb_counter_font: Handle::default(),
+            bomb_counter_colors: BoardAssets::default_colors(),
+            flag_material: SpriteMaterial::color(Color::RED),
+            bomb_material: SpriteMaterial::color(Color::BLACK),
+            menu_font: Handle::default(),
+            pause_cover_color: Color::rgb(0.18, 0.55, 0.34),
+            end_cover_color: Color::rgba(0.0, 0.0, 0.0, 0.6),
+            cover_text_color: Color::YELLOW,
+            question_mark_color: BoardAssets::default_question_mark_color(),
+            wrong_flag_color: BoardAssets::default_wrong_flag_color(),
+            tile_padding: None,
+            atlas: None,
+            counter_palette: CounterPalette::Theme,
+            counter_glyphs: false,
+        }
+    }
 }
 
 impl BoardAssets {
@@ -51,14 +119,174 @@ impl BoardAssets {
             Color::YELLOW,
             Color::ORANGE,
             Color::PURPLE,
+            Color::RED,
+            Color::CYAN,
+            Color::PINK,
         ]
     }
 
-    pub fn bomb_counter_color(&self, counter: u8) -> Color {
+    pub fn default_question_mark_color() -> Color {
+        Color::WHITE
+    }
+
+    pub fn default_wrong_flag_color() -> Color {
+        Color::RED
+    }
+
+    /// Spawns a square sprite of side `size`, drawn with the atlas cell chosen by `cell`
+    /// if there is an atlas, with `material` otherwise
+    pub(crate) fn spawn_sprite<'w, 's, 'a>(
+        &self,
+        parent: &'a mut ChildBuilder<'w, 's, '_>,
+        material: &SpriteMaterial,
+        cell: impl FnOnce(&AtlasCells) -> Option<usize>,
+        size: f32,
+        transform: Transform,
+    ) -> EntityCommands<'w, 's, 'a> {
+        let look = self.sprite_look(material, cell, Vec2::splat(size));
+        Self::spawn_look(parent, look, transform)
+    }
+
+    /// Spawns the sprite of a tile or a cover whose opposite sides are `size` apart.
+    /// Hexagonal tiles are drawn with the color of `material` in a hexagon, the atlas
+    /// cells and the textures are square and only draw square tiles
+    pub(crate) fn spawn_tile_sprite<'w, 's, 'a>(
+        &self,
+        parent: &'a mut ChildBuilder<'w, 's, '_>,
+        material: &SpriteMaterial,
+        cell: impl FnOnce(&AtlasCells) -> Option<usize>,
+        topology: Topology,
+        size: f32,
+        transform: Transform,
+    ) -> EntityCommands<'w, 's, 'a> {
+        let look = self.tile_look(material, cell, topology, size);
+        Self::spawn_look(parent, look, transform)
+    }
+
+    /// Draws an entity spawned by `spawn_tile_sprite` with these assets, its transform,
+    /// children and other components are kept
+    pub(crate) fn restyle_tile_sprite(
+        &self,
+        entity: &mut EntityCommands,
+        material: &SpriteMaterial,
+        cell: impl FnOnce(&AtlasCells) -> Option<usize>,
+        topology: Topology,
+        size: f32,
+    ) {
+        match self.tile_look(material, cell, topology, size) {
+            SpriteLook::Atlas(sprite, atlas) => {
+                entity.remove::<(Sprite, Handle<Image>)>().insert((sprite, atlas));
+            }
+            SpriteLook::Material(sprite, texture) => {
+                entity
+                    .remove::<(TextureAtlasSprite, Handle<TextureAtlas>)>()
+                    .insert((sprite, texture));
+            }
+        }
+    }
+
+    /// Atlas cell chosen by `cell` if there is an atlas, `material` otherwise
+    fn sprite_look(
+        &self,
+        material: &SpriteMaterial,
+        cell: impl FnOnce(&AtlasCells) -> Option<usize>,
+        size: Vec2,
+    ) -> SpriteLook {
+        let custom_size = Some(size);
+        if let Some(tile_atlas) = &self.atlas {
+            if let Some(index) = cell(&tile_atlas.cells) {
+                let sprite = TextureAtlasSprite {
+                    index,
+                    custom_size,
+                    ..Default::default()
+                };
+                return SpriteLook::Atlas(sprite, tile_atlas.atlas.clone());
+            }
+        }
+        let sprite = Sprite {
+            color: material.color,
+            custom_size,
+            ..Default::default()
+        };
+        SpriteLook::Material(sprite, material.texture.clone())
+    }
+
+    /// Look of a tile or a cover, see `spawn_tile_sprite`
+    fn tile_look(
+        &self,
+        material: &SpriteMaterial,
+        cell: impl FnOnce(&AtlasCells) -> Option<usize>,
+        topology: Topology,
+        size: f32,
+    ) -> SpriteLook {
+        let Some(texture) = crate::tile_shape::texture(topology) else {
+            return self.sprite_look(material, cell, Vec2::splat(size));
+        };
+        let (width, height) = topology.tile_extent();
+        let sprite = Sprite {
+            color: material.color,
+            custom_size: Some(Vec2::new(width, height) * size),
+            ..Default::default()
+        };
+        SpriteLook::Material(sprite, texture)
+    }
+
+    fn spawn_look<'w, 's, 'a>(
+        parent: &'a mut ChildBuilder<'w, 's, '_>,
+        look: SpriteLook,
+        transform: Transform,
+    ) -> EntityCommands<'w, 's, 'a> {
+        match look {
+            SpriteLook::Atlas(sprite, texture_atlas) => parent.spawn(SpriteSheetBundle {
+                sprite,
+                texture_atlas,
+                transform,
+                ..Default::default()
+            }),
+            SpriteLook::Material(sprite, texture) => parent.spawn(SpriteBundle {
+                sprite,
+                texture,
+                transform,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// The atlas draws the tiles and covers of `topology`, see `spawn_tile_sprite`
+    pub fn atlas_draws_tiles(&self, topology: Topology) -> bool {
+        self.atlas.is_some() && crate::tile_shape::texture(topology).is_none()
+    }
+
+    /// The atlas has a cell for the `count` counter
+    pub fn has_number_cell(&self, count: u16) -> bool {
+        self
+            .atlas
+            .as_ref()
+            .is_some_and(|tile_atlas| tile_atlas.cells.number(count).is_some())
+    }
+
+    /// `material`, or a plain `high_contrast` color with the `HighContrast` palette
+    pub(crate) fn contrasted(&self, material: &SpriteMaterial, high_contrast: Color) -> SpriteMaterial {
+        if self.counter_palette == CounterPalette::HighContrast {
+            return SpriteMaterial::color(high_contrast);
+        }
+        material.clone()
+    }
+
+    pub fn bomb_counter_color(&self, counter: u16) -> Color {
+        let colors = self
+            .counter_palette
+            .colors()
+            .unwrap_or(&self.bomb_counter_colors);
         let color_idx = counter.saturating_sub(1) as usize;
-        match self.bomb_counter_colors.get(color_idx) {
+        match colors.get(color_idx) {
             Some(color) => *color,
-            None => match self.bomb_counter_colors.last() {
+            // counters above the 8 square neighbours, from large neighbourhoods, get distinct
+            // hues spread by the golden angle
+            None if counter > 8 && !colors.is_empty() => {
+                Color::hsl((counter as f32 * 137.5) % 360.0, 0.8, 0.6)
+            }
+            None => match colors.last() {
                 Some(color) => *color, // after some counts of bomb will used the last color
                 None => Color::WHITE,  // empty list of colors
             },
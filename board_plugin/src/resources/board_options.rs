@@ -1,5 +1,17 @@
 use bevy::{prelude::*, ecs::system::Resource};
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::Coordinates;
+use bevy::log;
+use minesweeper_core::{Neighbourhood, TileMap, Topology};
+
+use crate::resources::BoardShape;
+
+/// Builds the random generator used for bombs placement from a seed
+pub type RngFactory = fn(u64) -> Box<dyn RngCore>;
 
 /// Tile size options
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,7 +19,7 @@ pub enum TileSize {
     /// Fixed tile size
     Fixed(f32),
 
-    /// Window adaptative size
+    /// Window adaptative size, `max` wins if `min` is larger
     Adaptive { min: f32, max: f32 },
 }
 
@@ -21,6 +33,70 @@ pub enum BoardPosition {
     Custom(Vec3),
 }
 
+/// When and how bombs are placed on the board
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum GenerationMode {
+    /// Bombs are placed when the board is created
+    #[default]
+    Immediate,
+
+    /// Bombs are placed on the first uncovered tile, the clicked tile is never a bomb.
+    /// With `safe_neighbourhood` its 8 neighbours are free of bombs too.
+    FirstClick { safe_neighbourhood: bool },
+
+    /// Bombs are placed on the first uncovered tile so that the board can be solved
+    /// from there without guessing. Gives up with the last tried layout when
+    /// `max_attempts` or `max_duration` is reached, and sends a `NoGuessFailedEvent`.
+    NoGuess {
+        max_attempts: u32,
+        max_duration: Duration,
+    },
+}
+
+/// Keyboard cursor behaviour at the board edges
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CursorMovement {
+    /// The cursor stops at the edge
+    #[default]
+    Clamp,
+
+    /// The cursor continues on the opposite edge
+    Wrap,
+}
+
+impl CursorMovement {
+    /// Moves `from` by `delta` to the next tile of `tile_map`, the void cells of a masked
+    /// board are skipped. Stays on `from` if there is no tile in that direction
+    pub fn step(&self, from: Coordinates, delta: (i8, i8), tile_map: &TileMap) -> Coordinates {
+        let (width, height) = (tile_map.width(), tile_map.height());
+        let step = |value: u16, delta: i8, size: u16| -> u16 {
+            let target = value as i32 + delta as i32;
+            let max = size.max(1) as i32 - 1;
+            match self {
+                CursorMovement::Clamp => target.clamp(0, max) as u16,
+                CursorMovement::Wrap => target.rem_euclid(max + 1) as u16,
+            }
+        };
+
+        let mut current = from;
+        for _ in 0..width.max(height) {
+            let next = Coordinates {
+                x: step(current.x, delta.0, width),
+                y: step(current.y, delta.1, height),
+            };
+            // stopped by an edge or back to the start
+            if next == current || next == from {
+                break;
+            }
+            if tile_map.is_tile(next) {
+                return next;
+            }
+            current = next;
+        }
+        from
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoardSize {
    pub columns : u16,
@@ -32,8 +108,11 @@ pub struct BoardSize {
 // adopted 0.8 to 0.8 ([derive(Resource)])
 #[derive(Debug, Clone, Serialize, Deserialize, Resource)]
 pub struct BoardOptions<T : States> {
-    /// Tile map size
+    /// Tile map size, replaced by the size of a template or image `shape`
     pub map_size: BoardSize,
+    /// Outline of the board
+    #[serde(default)]
+    pub shape: BoardShape,
     // bomb's count
     pub bomb_count: u16,
     /// Board world position
@@ -42,39 +121,90 @@ pub struct BoardOptions<T : States> {
     pub tile_size: TileSize,
     /// Padding (inner offset) between tiles
     pub tile_padding: f32,
+    /// Square or hexagonal tiles
+    #[serde(default)]
+    pub topology: Topology,
+    /// Toroidal board: tiles on opposite edges are neighbours
+    #[serde(default)]
+    pub wrapping: bool,
+    /// Tiles counted by the numbers, the adjacent ones by default
+    #[serde(default)]
+    pub neighbourhood: Neighbourhood,
     /// Does the board generate a safe place to start
     pub safe_start: bool,
+    /// When bombs are placed
+    pub generation: GenerationMode,
+    /// Seed of the bombs layout. A random seed is picked for each board if `None`
+    pub seed: Option<u64>,
+    /// Delay between the waves of an uncovered empty region, revealed at once if `None`
+    pub reveal_animation: Option<Duration>,
+    /// Keyboard cursor behaviour at the board edges
+    #[serde(default)]
+    pub cursor_movement: CursorMovement,
+    /// A flag toggle goes from a flag to a question mark before removing it
+    #[serde(default)]
+    pub question_marks: bool,
+    /// Custom random generator, `StdRng` is used if `None`
+    #[serde(skip)]
+    pub rng: Option<RngFactory>,
 
     /// State with active game
     pub game_state: T,
 
     /// State with paused game
     pub pause_state: T,
+
+    /// State with won or lost game
+    pub end_state: T,
 }
 
 impl <T: States> BoardOptions<T> {
-    // here is place for simple optimization -> make as read-only field and calcualte in ::new()
-    pub fn tile_size_px(&self) -> f32 {
-        return match self.tile_size {
+    /// Tile map without bombs with the shape and rules of the options, `shape_image` is the
+    /// image of `BoardShape::Image`. Falls back to the `map_size` rectangle if the shape
+    /// can't be loaded
+    pub fn empty_tile_map(&self, shape_image: Option<&Image>) -> TileMap {
+        let mask = self.shape.mask(&self.map_size, shape_image).unwrap_or_else(|e| {
+            log::error!("Failed to load the board shape: {}", e);
+            BoardShape::Rectangle.mask(&self.map_size, None).unwrap_or_default()
+        });
+        TileMap::with_topology(mask.width(), mask.height(), self.topology)
+            .with_wrapping(self.wrapping)
+            .with_neighbourhood(self.neighbourhood.clone())
+            .with_mask(&mask)
+    }
+
+    /// Tile size in pixels. Adaptive size fits the board of `tile_map` into `window_size`
+    pub fn tile_size_px(&self, window_size: Vec2, tile_map: &TileMap) -> f32 {
+        match self.tile_size {
             TileSize::Fixed(size) => size,
-            TileSize::Adaptive { .. } => panic!(
-                "Not supported in this commit due to WindowDescriptor is not available as resource"
-            )
+            TileSize::Adaptive { min, max } => {
+                let (width, height) = tile_map.extent();
+                let max_width = window_size.x / width.max(1.0);
+                // the centered board keeps a row on each side, the HUD is above the board
+                let max_height = window_size.y / (height + 2.0);
+                // unlike clamp, doesn't panic on bounds in the wrong order
+                max_width.min(max_height).max(min).min(max)
+            }
         }
     }
 
-    pub fn board_size(&self) -> Vec2 {
-         let tile_size_pixels = self.tile_size_px();
-
-        return Vec2::new(
-            self.map_size.columns as f32 * tile_size_pixels,
-            self.map_size.rows as f32 * tile_size_pixels
-        )
+    /// Returns the seed for a new board: the configured one or a random one
+    pub fn board_seed(&self) -> u64 {
+        match self.seed {
+            Some(seed) => seed,
+            None => thread_rng().gen(),
+        }
     }
 
-    pub fn board_position_px(&self, z_layer : f32) -> Vec3 {
-        let board_size = self.board_size();
+    /// Creates the random generator for bombs placement, seeded with `seed`
+    pub fn board_rng(&self, seed: u64) -> Box<dyn RngCore> {
+        match self.rng {
+            Some(factory) => factory(seed),
+            None => Box::new(StdRng::seed_from_u64(seed)),
+        }
+    }
 
+    pub fn board_position_px(&self, board_size: Vec2, z_layer : f32) -> Vec3 {
         match self.position {
             BoardPosition::Centered { offset } => {
                 Vec3 {
@@ -0,0 +1,97 @@
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+
+use bevy::asset::Handle;
+use bevy::ecs::system::Resource;
+use bevy::render::texture::Image;
+use minesweeper_core::{Mask, MaskError};
+use serde::{Deserialize, Serialize};
+
+use crate::resources::BoardSize;
+
+/// Alpha below which a pixel of a mask image is a void cell
+const IMAGE_ALPHA_THRESHOLD: u8 = 128;
+
+/// Failure to build the mask of a `BoardShape`
+#[derive(Debug)]
+pub enum ShapeError {
+    /// The image asset of `BoardShape::Image` failed to load
+    NotLoaded(PathBuf),
+    /// The file is not a supported image
+    Image(String),
+    Mask(MaskError),
+}
+
+impl Display for ShapeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeError::NotLoaded(path) => write!(f, "failed to load {}", path.display()),
+            ShapeError::Image(e) => write!(f, "invalid mask image: {}", e),
+            ShapeError::Mask(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+impl From<MaskError> for ShapeError {
+    fn from(e: MaskError) -> Self {
+        ShapeError::Mask(e)
+    }
+}
+
+/// Outline of the board. Void cells are neither tiles nor neighbours
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum BoardShape {
+    /// Full rectangle of `BoardOptions::map_size`
+    #[default]
+    Rectangle,
+
+    /// ASCII template, see `Mask::from_ascii`. Its size replaces `map_size`
+    Template(String),
+
+    /// Image with a pixel per cell, transparent pixels are void cells. The path is in the
+    /// assets folder like the textures of the themes, the board is created once the image
+    /// is loaded. Its size replaces `map_size`
+    Image(PathBuf),
+}
+
+impl BoardShape {
+    /// Tiles of the shape. `image` is the loaded asset of `BoardShape::Image`
+    pub fn mask(&self, map_size: &BoardSize, image: Option<&Image>) -> Result<Mask, ShapeError> {
+        match self {
+            BoardShape::Rectangle => Ok(Mask::full(map_size.columns, map_size.rows)),
+            BoardShape::Template(template) => Ok(Mask::from_ascii(template)?),
+            BoardShape::Image(path) => match image {
+                Some(image) => mask_from_image(image.clone()),
+                None => Err(ShapeError::NotLoaded(path.clone())),
+            },
+        }
+    }
+}
+
+/// Image of the last `BoardShape::Image`, inserted by the plugin to keep it loaded for the
+/// next boards
+#[derive(Resource)]
+pub struct ShapeImage(pub Handle<Image>);
+
+/// Mask with a cell per pixel, the top row of the image is the top row of the board
+pub fn mask_from_image(image: Image) -> Result<Mask, ShapeError> {
+    let pixels = image
+        .try_into_dynamic()
+        .map_err(|e| ShapeError::Image(e.to_string()))?
+        .to_rgba8();
+    let (width, height) = pixels.dimensions();
+    if width > u16::MAX as u32 || height > u16::MAX as u32 {
+        return Err(MaskError::TooLarge.into());
+    }
+
+    let mask = Mask::from_fn(width as u16, height as u16, |c| {
+        let pixel = pixels.get_pixel(c.x as u32, height - 1 - c.y as u32);
+        pixel.0[3] >= IMAGE_ALPHA_THRESHOLD
+    });
+    if mask.void_cells().len() == width as usize * height as usize {
+        return Err(MaskError::Empty.into());
+    }
+    Ok(mask)
+}
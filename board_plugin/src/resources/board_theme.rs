@@ -0,0 +1,207 @@
+use std::fmt::{self, Display, Formatter};
+
+use bevy::asset::io::Reader;
+use bevy::asset::{Asset, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use bevy::utils::BoxedFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::{AtlasCells, BoardAssets, CounterPalette, SpriteMaterial, TileAtlas};
+
+/// Failure to load a `BoardTheme`
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(std::io::Error),
+    /// The file is not a valid theme
+    Ron(ron::error::SpannedError),
+    /// A cell of the atlas is outside its grid of `cell_count` cells
+    AtlasCell { index: usize, cell_count: usize },
+}
+
+impl Display for ThemeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(e) => write!(f, "{}", e),
+            ThemeError::Ron(e) => write!(f, "invalid theme: {}", e),
+            ThemeError::AtlasCell { index, cell_count } => {
+                write!(f, "atlas cell {} is outside the {} cells of the grid", index, cell_count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+impl From<std::io::Error> for ThemeError {
+    fn from(e: std::io::Error) -> Self {
+        ThemeError::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for ThemeError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        ThemeError::Ron(e)
+    }
+}
+
+/// Color and texture of a sprite in a theme file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeMaterial {
+    pub color: Color,
+    /// Path in the assets folder, the sprite is plain `color` without texture
+    #[serde(default)]
+    pub texture: Option<String>,
+}
+
+impl ThemeMaterial {
+    fn load(&self, load_context: &mut LoadContext) -> SpriteMaterial {
+        SpriteMaterial {
+            color: self.color,
+            texture: self
+                .texture
+                .as_ref()
+                .map(|path| load_context.load(path))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Texture atlas of a theme file, a grid of `columns` x `rows` cells of `cell_size` pixels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeAtlas {
+    pub texture: String,
+    pub cell_size: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+    /// Gap between the cells
+    #[serde(default)]
+    pub padding: Option<Vec2>,
+    /// Position of the first cell in the texture
+    #[serde(default)]
+    pub offset: Option<Vec2>,
+    pub cells: AtlasCells,
+}
+
+impl ThemeAtlas {
+    /// Checks that the cells are in the grid, then loads the texture
+    fn load(&self, load_context: &mut LoadContext) -> Result<TileAtlas, ThemeError> {
+        let cell_count = self.columns * self.rows;
+        if let Some(index) = self.cells.indices().find(|index| *index >= cell_count) {
+            return Err(ThemeError::AtlasCell { index, cell_count });
+        }
+        let texture = load_context.load(&self.texture);
+        let atlas = TextureAtlas::from_grid(
+            texture,
+            self.cell_size,
+            self.columns,
+            self.rows,
+            self.padding,
+            self.offset,
+        );
+        Ok(TileAtlas {
+            atlas: load_context.add_labeled_asset("atlas".to_string(), atlas),
+            cells: self.cells.clone(),
+        })
+    }
+}
+
+/// Content of a `*.theme.ron` file, see `BoardTheme`. Paths are in the assets folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardThemeFile {
+    pub label: String,
+    pub board: ThemeMaterial,
+    pub tile: ThemeMaterial,
+    pub covered_tile: ThemeMaterial,
+    pub flag: ThemeMaterial,
+    pub bomb: ThemeMaterial,
+    pub bomb_counter_font: String,
+    /// Colors of the counters from 1. The last one is reused up to 8, higher counters
+    /// of large neighbourhoods get generated hues
+    #[serde(default = "BoardAssets::default_colors")]
+    pub bomb_counter_colors: Vec<Color>,
+    pub menu_font: String,
+    pub pause_cover_color: Color,
+    pub end_cover_color: Color,
+    pub cover_text_color: Color,
+    /// Question marks drawn as text, without a question mark cell in the atlas
+    #[serde(default = "BoardAssets::default_question_mark_color")]
+    pub question_mark_color: Color,
+    /// Tint of the covers of the wrong flags, without a wrong flag cell in the atlas
+    #[serde(default = "BoardAssets::default_wrong_flag_color")]
+    pub wrong_flag_color: Color,
+    /// Replaces `BoardOptions::tile_padding`
+    #[serde(default)]
+    pub tile_padding: Option<f32>,
+    #[serde(default)]
+    pub atlas: Option<ThemeAtlas>,
+}
+
+impl BoardThemeFile {
+    /// Board assets of the theme, the textures and fonts are loaded with `load_context`
+    fn load(&self, load_context: &mut LoadContext) -> Result<BoardAssets, ThemeError> {
+        let atlas = self.atlas.as_ref().map(|atlas| atlas.load(load_context)).transpose()?;
+        Ok(BoardAssets {
+            label: self.label.clone(),
+            board_material: self.board.load(load_context),
+            tile_material: self.tile.load(load_context),
+            covered_tile_material: self.covered_tile.load(load_context),
+            bomb_counter_font: load_context.load(&self.bomb_counter_font),
+            bomb_counter_colors: self.bomb_counter_colors.clone(),
+            flag_material: self.flag.load(load_context),
+            bomb_material: self.bomb.load(load_context),
+            menu_font: load_context.load(&self.menu_font),
+            pause_cover_color: self.pause_cover_color,
+            end_cover_color: self.end_cover_color,
+            cover_text_color: self.cover_text_color,
+            question_mark_color: self.question_mark_color,
+            wrong_flag_color: self.wrong_flag_color,
+            tile_padding: self.tile_padding,
+            atlas,
+            counter_palette: CounterPalette::Theme,
+            counter_glyphs: false,
+        })
+    }
+}
+
+/// Board assets loaded from a `*.theme.ron` file
+#[derive(Debug, Clone, Asset, TypePath)]
+pub struct BoardTheme {
+    pub assets: BoardAssets,
+}
+
+/// Loader of the `*.theme.ron` files
+#[derive(Debug, Default)]
+pub struct BoardThemeLoader;
+
+impl AssetLoader for BoardThemeLoader {
+    type Asset = BoardTheme;
+    type Settings = ();
+    type Error = ThemeError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let file: BoardThemeFile = ron::de::from_bytes(&bytes)?;
+            Ok(BoardTheme {
+                assets: file.load(load_context)?,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+/// Theme followed by `BoardAssets`. The board is restyled when the theme is loaded,
+/// modified on disk (with the `file_watcher` feature of Bevy, e.g. `cargo run --features dev`)
+/// or replaced by another one
+#[derive(Debug, Clone, Default, Resource)]
+pub struct ActiveTheme(pub Handle<BoardTheme>);
@@ -0,0 +1,11 @@
+use bevy::prelude::*;
+
+use crate::Coordinates;
+
+/// Tile selected with the keyboard
+#[derive(Debug, Clone, Copy, Default, Resource)]
+pub struct BoardCursor {
+    pub coordinates: Coordinates,
+    /// The highlight is shown once the keyboard is used and hidden on mouse clicks
+    pub visible: bool,
+}
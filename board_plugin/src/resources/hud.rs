@@ -0,0 +1,12 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+/// Values shown by the HUD, updated every frame
+#[derive(Debug, Clone, Copy, Default, Resource)]
+pub struct Hud {
+    /// Time since the first reveal of the player, pauses are excluded
+    pub elapsed: Duration,
+    /// Bombs count minus flags count, negative if there are too many flags
+    pub remaining_bombs: i32,
+}
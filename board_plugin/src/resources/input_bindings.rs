@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+use bevy::input::gamepad::GamepadButtonType;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Player action which can be bound to an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    /// Uncover the tile under the mouse or the cursor
+    Reveal,
+    /// Set or remove a flag
+    Flag,
+    /// Uncover the neighbours of a counter with enough flags
+    Chord,
+    CursorUp,
+    CursorDown,
+    CursorLeft,
+    CursorRight,
+    /// Drag the board by moving the mouse while held, the middle button or Shift by default.
+    /// Mouse buttons also bound to another action act on their release, unless the board
+    /// was dragged
+    Pan,
+    /// Pause or resume the game
+    Pause,
+    /// Replace the board by a new one during the game
+    Regenerate,
+    /// Start a new board from the result overlay
+    Restart,
+    /// Move the cursor to a tile which is safe to uncover without guessing
+    Hint,
+    /// Cancel the last reveal, chord or flag, also from the result overlay
+    Undo,
+    /// Save the game. Not handled by the plugin, the application sends `SaveGameEvent`
+    Save,
+    /// Load a saved game. Not handled by the plugin, the application sends `LoadGameEvent`
+    Load,
+    /// Switch to the next theme. Not handled by the plugin, the application sets `ActiveTheme`
+    NextTheme,
+    /// Switch to the next `CounterPalette`. Not handled by the plugin
+    NextPalette,
+    /// Show or hide the counter glyphs. Not handled by the plugin
+    ToggleGlyphs,
+}
+
+/// Physical input triggering an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// Button of any connected gamepad
+    Gamepad(GamepadButtonType),
+}
+
+impl Display for InputBinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InputBinding::Key(key) => write!(f, "{:?}", key),
+            InputBinding::Mouse(button) => write!(f, "{:?} mouse button", button),
+            InputBinding::Gamepad(button_type) => write!(f, "{:?} button", button_type),
+        }
+    }
+}
+
+/// Inputs of each action, consulted by all input systems of the plugin.
+/// Can be loaded from a file with serde and inserted before the plugin
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputBindings {
+    pub bindings: HashMap<InputAction, Vec<InputBinding>>,
+    /// Time a cursor direction is held before the cursor moves again
+    pub repeat_delay: Duration,
+    /// Time between the repeated cursor moves of a held direction
+    pub repeat_interval: Duration,
+    /// Tilt of the left gamepad stick moving the cursor, from 0 to 1
+    pub stick_threshold: f32,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use GamepadButtonType as Pad;
+        use InputBinding::{Gamepad, Key, Mouse};
+        let bindings = HashMap::from([
+            (
+                InputAction::Reveal,
+                vec![
+                    Mouse(MouseButton::Left),
+                    Key(KeyCode::Space),
+                    Key(KeyCode::Return),
+                    Gamepad(Pad::South),
+                ],
+            ),
+            (
+                InputAction::Flag,
+                vec![Mouse(MouseButton::Right), Key(KeyCode::F), Gamepad(Pad::East)],
+            ),
+            (
+                InputAction::Chord,
+                vec![Mouse(MouseButton::Middle), Key(KeyCode::C), Gamepad(Pad::West)],
+            ),
+            (
+                InputAction::CursorUp,
+                vec![Key(KeyCode::Up), Key(KeyCode::W), Key(KeyCode::K), Gamepad(Pad::DPadUp)],
+            ),
+            (
+                InputAction::CursorDown,
+                vec![
+                    Key(KeyCode::Down),
+                    Key(KeyCode::S),
+                    Key(KeyCode::J),
+                    Gamepad(Pad::DPadDown),
+                ],
+            ),
+            (
+                InputAction::CursorLeft,
+                vec![
+                    Key(KeyCode::Left),
+                    Key(KeyCode::A),
+                    Key(KeyCode::H),
+                    Gamepad(Pad::DPadLeft),
+                ],
+            ),
+            (
+                InputAction::CursorRight,
+                vec![
+                    Key(KeyCode::Right),
+                    Key(KeyCode::D),
+                    Key(KeyCode::L),
+                    Gamepad(Pad::DPadRight),
+                ],
+            ),
+            (
+                InputAction::Pan,
+                vec![
+                    Mouse(MouseButton::Middle),
+                    Key(KeyCode::ShiftLeft),
+                    Key(KeyCode::ShiftRight),
+                ],
+            ),
+            (InputAction::Pause, vec![Key(KeyCode::P), Gamepad(Pad::Start)]),
+            (InputAction::Regenerate, vec![Key(KeyCode::G)]),
+            (InputAction::Restart, vec![Key(KeyCode::R), Gamepad(Pad::Select)]),
+            (InputAction::Hint, vec![Key(KeyCode::Slash), Gamepad(Pad::North)]),
+            (InputAction::Undo, vec![Key(KeyCode::U), Gamepad(Pad::LeftTrigger)]),
+            (InputAction::Save, vec![Key(KeyCode::F5)]),
+            (InputAction::Load, vec![Key(KeyCode::F9)]),
+            (InputAction::NextTheme, vec![Key(KeyCode::T)]),
+            (InputAction::NextPalette, vec![Key(KeyCode::V)]),
+            (InputAction::ToggleGlyphs, vec![Key(KeyCode::B)]),
+        ]);
+        Self {
+            bindings,
+            repeat_delay: Duration::from_millis(300),
+            repeat_interval: Duration::from_millis(100),
+            stick_threshold: 0.5,
+        }
+    }
+}
+
+impl InputBindings {
+    /// Inputs bound to `action`, empty if the action is not bound
+    pub fn bindings(&self, action: InputAction) -> &[InputBinding] {
+        self.bindings.get(&action).map_or(&[], |b| b.as_slice())
+    }
+
+    /// Inputs bound to `action` for the messages to the player, e.g. "R or Select button".
+    /// `None` if the action is not bound
+    pub fn describe(&self, action: InputAction) -> Option<String> {
+        let bindings = self.bindings(action);
+        if bindings.is_empty() {
+            return None;
+        }
+        let names: Vec<String> = bindings.iter().map(InputBinding::to_string).collect();
+        Some(names.join(" or "))
+    }
+
+    /// Is `binding` bound to `action`
+    pub fn is_bound(&self, action: InputAction, binding: InputBinding) -> bool {
+        self.bindings(action).contains(&binding)
+    }
+
+    /// Mouse buttons bound to `action`
+    pub fn mouse_buttons(&self, action: InputAction) -> impl Iterator<Item = MouseButton> + '_ {
+        self.bindings(action).iter().filter_map(|b| match b {
+            InputBinding::Mouse(button) => Some(*button),
+            _ => None,
+        })
+    }
+}
@@ -1,8 +1,22 @@
-pub(crate) mod tile;
-pub(crate) mod tile_map;
 pub use board_assests::*;
 pub mod board;
 pub use board_options::*;
+pub use board_shape::*;
+pub use board_theme::*;
+pub use cursor::BoardCursor;
+pub use hud::Hud;
+pub use input_bindings::*;
+pub use palette::CounterPalette;
+pub use saved_game::*;
+pub use tile_atlas::*;
 
 mod board_assests;
-mod board_options;
\ No newline at end of file
+mod board_options;
+mod board_shape;
+mod board_theme;
+mod cursor;
+mod hud;
+mod input_bindings;
+mod palette;
+mod saved_game;
+mod tile_atlas;
@@ -0,0 +1,95 @@
+use bevy::prelude::Color;
+use serde::{Deserialize, Serialize};
+
+/// Okabe-Ito palette with white, distinct with a deuteranopia
+const DEUTERANOPIA: [Color; 8] = [
+    Color::rgb(0.34, 0.71, 0.91),
+    Color::rgb(0.90, 0.62, 0.0),
+    Color::rgb(0.94, 0.89, 0.26),
+    Color::rgb(0.0, 0.45, 0.70),
+    Color::rgb(0.0, 0.62, 0.45),
+    Color::rgb(0.84, 0.37, 0.0),
+    Color::rgb(0.80, 0.47, 0.65),
+    Color::rgb(1.0, 1.0, 1.0),
+];
+
+/// Okabe-Ito palette without vermillion, which looks dark with a protanopia
+const PROTANOPIA: [Color; 8] = [
+    Color::rgb(0.34, 0.71, 0.91),
+    Color::rgb(0.90, 0.62, 0.0),
+    Color::rgb(0.94, 0.89, 0.26),
+    Color::rgb(0.0, 0.45, 0.70),
+    Color::rgb(0.0, 0.62, 0.45),
+    Color::rgb(0.80, 0.47, 0.65),
+    Color::rgb(1.0, 1.0, 1.0),
+    Color::rgb(0.6, 0.6, 0.6),
+];
+
+/// Reds and cyans of several lightnesses, blue and yellow look alike with a tritanopia
+const TRITANOPIA: [Color; 8] = [
+    Color::rgb(0.3, 0.85, 0.85),
+    Color::rgb(1.0, 0.35, 0.35),
+    Color::rgb(1.0, 1.0, 1.0),
+    Color::rgb(1.0, 0.7, 0.8),
+    Color::rgb(0.0, 0.55, 0.55),
+    Color::rgb(0.85, 0.1, 0.45),
+    Color::rgb(0.6, 0.6, 0.6),
+    Color::rgb(0.7, 1.0, 1.0),
+];
+
+/// Saturated and light colors on black tiles
+const HIGH_CONTRAST: [Color; 8] = [
+    Color::rgb(1.0, 1.0, 1.0),
+    Color::rgb(1.0, 1.0, 0.0),
+    Color::rgb(0.0, 1.0, 1.0),
+    Color::rgb(1.0, 0.0, 1.0),
+    Color::rgb(0.5, 1.0, 0.0),
+    Color::rgb(1.0, 0.6, 0.0),
+    Color::rgb(0.6, 0.7, 1.0),
+    Color::rgb(1.0, 0.4, 0.4),
+];
+
+/// Colors of the bombs counters, see `BoardAssets::counter_palette`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CounterPalette {
+    /// `BoardAssets::bomb_counter_colors` of the theme
+    #[default]
+    Theme,
+    /// Red-green deficiency with a weak green
+    Deuteranopia,
+    /// Red-green deficiency with a weak red
+    Protanopia,
+    /// Blue-yellow deficiency
+    Tritanopia,
+    /// Light counters on black tiles, plain light covers and white gaps.
+    /// Replaces the colors and textures of the board, tiles and covers drawn without atlas
+    HighContrast,
+}
+
+impl CounterPalette {
+    /// Every palette, in the order of `next`
+    pub const ALL: [CounterPalette; 5] = [
+        CounterPalette::Theme,
+        CounterPalette::Deuteranopia,
+        CounterPalette::Protanopia,
+        CounterPalette::Tritanopia,
+        CounterPalette::HighContrast,
+    ];
+
+    /// Counter colors from 1, `None` for the theme colors
+    pub fn colors(&self) -> Option<&'static [Color]> {
+        match self {
+            CounterPalette::Theme => None,
+            CounterPalette::Deuteranopia => Some(&DEUTERANOPIA),
+            CounterPalette::Protanopia => Some(&PROTANOPIA),
+            CounterPalette::Tritanopia => Some(&TRITANOPIA),
+            CounterPalette::HighContrast => Some(&HIGH_CONTRAST),
+        }
+    }
+
+    /// Following palette, back to the first after the last
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|p| p == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
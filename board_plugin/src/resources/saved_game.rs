@@ -0,0 +1,350 @@
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+
+use bevy::prelude::States;
+use minesweeper_core::{BitSet, Coordinates, Game, Tile, TileMap};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::board::{Board, BoardStats};
+use crate::resources::BoardOptions;
+
+/// Version of the save file format, increased on every incompatible change
+pub const SAVE_VERSION: u32 = 2;
+
+/// Failure to save or load a game
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Format(ron::Error),
+    /// The file is written by another version of the game
+    Version { found: u32 },
+    /// The content doesn't describe a consistent game, e.g. an edited file
+    Invalid(String),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "{}", e),
+            SaveError::Format(e) => write!(f, "invalid save file: {}", e),
+            SaveError::Version { found } => write!(
+                f,
+                "unsupported save file version {} (expected {})",
+                found, SAVE_VERSION
+            ),
+            SaveError::Invalid(reason) => write!(f, "invalid save file: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<ron::Error> for SaveError {
+    fn from(e: ron::Error) -> Self {
+        SaveError::Format(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for SaveError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        SaveError::Format(e.code)
+    }
+}
+
+/// Only the version of a save file, read before the full content
+#[derive(Deserialize)]
+#[serde(rename = "SavedGame")]
+struct SaveHeader {
+    version: u32,
+}
+
+/// Full state of a game in progress, stored as RON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: States + Serialize + DeserializeOwned")]
+pub struct SavedGame<T: States> {
+    pub version: u32,
+    pub options: BoardOptions<T>,
+    pub seed: u64,
+    /// Are the bombs placed, see `GenerationMode`
+    pub generated: bool,
+    pub tile_map: TileMap,
+    pub covered_tiles: Vec<Coordinates>,
+    pub marked_tiles: Vec<Coordinates>,
+    /// Tiles with a question mark, see `BoardOptions::question_marks`
+    #[serde(default)]
+    pub questioned_tiles: Vec<Coordinates>,
+    pub stats: BoardStats,
+}
+
+impl<T: States + Serialize + DeserializeOwned> SavedGame<T> {
+    pub fn new(board: &Board, options: &BoardOptions<T>) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            options: options.clone(),
+            seed: board.seed,
+            generated: board.game.is_generated(),
+            tile_map: board.game.tile_map().clone(),
+            covered_tiles: board.game.covered_tiles().collect(),
+            marked_tiles: board.game.flagged_tiles().collect(),
+            questioned_tiles: board.game.questioned_tiles().collect(),
+            stats: board.stats,
+        }
+    }
+
+    /// Rebuilds the game model, bombs are not placed again
+    pub fn game(&self) -> Result<Game, SaveError> {
+        self.validate()?;
+        Ok(Game::restore(
+            self.tile_map.clone(),
+            self.generated,
+            self.covered_tiles.clone(),
+            self.marked_tiles.clone(),
+            self.questioned_tiles.clone(),
+        )
+        .with_question_marks(self.options.question_marks))
+    }
+
+    /// Options of the saved board on top of `current`: the rules and the look of the
+    /// board come from the save, the states and the random generator of the application
+    /// are kept
+    pub fn board_options(&self, current: &BoardOptions<T>) -> BoardOptions<T> {
+        BoardOptions {
+            rng: current.rng,
+            game_state: current.game_state.clone(),
+            pause_state: current.pause_state.clone(),
+            end_state: current.end_state.clone(),
+            ..self.options.clone()
+        }
+    }
+
+    /// Checks that the tiles and the bombs agree with the tile map
+    fn validate(&self) -> Result<(), SaveError> {
+        let tile_map = &self.tile_map;
+        if let Some(c) = self.covered_tiles.iter().find(|c| !tile_map.is_tile(**c)) {
+            return Err(SaveError::Invalid(format!("covered tile {} is not on the map", c)));
+        }
+        if let Some(c) = self.marked_tiles.iter().find(|c| !tile_map.is_tile(**c)) {
+            return Err(SaveError::Invalid(format!("marked tile {} is not on the map", c)));
+        }
+        // sets of tile indices, the lists may hold millions of tiles
+        let tile_set = |tiles: &[Coordinates]| {
+            let mut set = BitSet::new(tile_map.len());
+            for index in tiles.iter().filter_map(|c| tile_map.index_of(*c)) {
+                set.set(index, true);
+            }
+            set
+        };
+        let covered = tile_set(&self.covered_tiles);
+        let marked = tile_set(&self.marked_tiles);
+        let contains = |set: &BitSet, c: &Coordinates| {
+            tile_map.index_of(*c).is_some_and(|index| set.contains(index))
+        };
+        if let Some(c) = self.marked_tiles.iter().find(|c| !contains(&covered, c)) {
+            return Err(SaveError::Invalid(format!("marked tile {} is not covered", c)));
+        }
+        let misplaced_question = self
+            .questioned_tiles
+            .iter()
+            .find(|c| !contains(&covered, c) || contains(&marked, c));
+        if let Some(c) = misplaced_question {
+            return Err(SaveError::Invalid(format!(
+                "question mark on {} which is not covered or is flagged",
+                c
+            )));
+        }
+        let bombs = tile_map.tiles().filter(|c| tile_map[*c] == Tile::Bomb).count();
+        if bombs != tile_map.bomb_count() as usize {
+            return Err(SaveError::Invalid(format!(
+                "{} bombs on the map, {} expected",
+                bombs,
+                tile_map.bomb_count()
+            )));
+        }
+        if !self.generated && bombs > 0 {
+            return Err(SaveError::Invalid("bombs on a board which is not generated".to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn to_ron(&self) -> Result<String, SaveError> {
+        Ok(ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?)
+    }
+
+    pub fn from_ron(content: &str) -> Result<Self, SaveError> {
+        let header: SaveHeader = ron::from_str(content)?;
+        if header.version != SAVE_VERSION {
+            return Err(SaveError::Version {
+                found: header.version,
+            });
+        }
+        let saved: Self = ron::from_str(content)?;
+        saved.validate()?;
+        Ok(saved)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SaveError> {
+        std::fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, SaveError> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::resources::{BoardPosition, BoardShape, BoardSize, CursorMovement, GenerationMode, TileSize};
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, States, Serialize, Deserialize)]
+    enum TestState {
+        #[default]
+        Game,
+        Pause,
+        End,
+    }
+
+    fn options() -> BoardOptions<TestState> {
+        BoardOptions {
+            map_size: BoardSize { columns: 8, rows: 6 },
+            shape: BoardShape::Rectangle,
+            bomb_count: 7,
+            position: BoardPosition::default(),
+            tile_size: TileSize::Fixed(20.0),
+            tile_padding: 1.0,
+            topology: Default::default(),
+            wrapping: false,
+            neighbourhood: Default::default(),
+            safe_start: false,
+            generation: GenerationMode::Immediate,
+            seed: Some(42),
+            reveal_animation: None,
+            cursor_movement: CursorMovement::Clamp,
+            question_marks: true,
+            rng: None,
+            game_state: TestState::Game,
+            pause_state: TestState::Pause,
+            end_state: TestState::End,
+        }
+    }
+
+    fn saved_game() -> SavedGame<TestState> {
+        let mut tile_map = TileMap::empty(8, 6);
+        tile_map.set_bombs(7, &mut StdRng::seed_from_u64(42));
+        let mut game = Game::new(tile_map);
+        let safe = game.tile_map().tiles().find(|c| !game.tile_map().is_bomb_at(*c)).unwrap();
+        game.reveal(safe);
+        let bomb = game.tile_map().tiles().find(|c| game.tile_map().is_bomb_at(*c)).unwrap();
+        game.toggle_flag(bomb);
+
+        SavedGame {
+            version: SAVE_VERSION,
+            options: options(),
+            seed: 42,
+            generated: true,
+            tile_map: game.tile_map().clone(),
+            covered_tiles: game.covered_tiles().collect(),
+            marked_tiles: game.flagged_tiles().collect(),
+            questioned_tiles: Vec::new(),
+            stats: BoardStats {
+                elapsed: Duration::from_secs(12),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn round_trip_keeps_the_game() {
+        let saved = saved_game();
+        let loaded = SavedGame::<TestState>::from_ron(&saved.to_ron().unwrap()).unwrap();
+
+        assert_eq!(loaded.seed, 42);
+        assert_eq!(loaded.stats.elapsed, Duration::from_secs(12));
+        assert_eq!(loaded.options.end_state, TestState::End);
+        assert_eq!(loaded.covered_tiles, saved.covered_tiles);
+        assert_eq!(loaded.marked_tiles, saved.marked_tiles);
+
+        let (game, loaded_game) = (saved.game().unwrap(), loaded.game().unwrap());
+        for c in game.tile_map().tiles() {
+            assert_eq!(game.tile_map()[c], loaded_game.tile_map()[c]);
+        }
+        assert_eq!(game.state(), loaded_game.state());
+    }
+
+    #[test]
+    fn other_version_is_rejected() {
+        let mut saved = saved_game();
+        saved.version = SAVE_VERSION + 1;
+        let result = SavedGame::<TestState>::from_ron(&saved.to_ron().unwrap());
+        assert!(matches!(result, Err(SaveError::Version { found }) if found == SAVE_VERSION + 1));
+    }
+
+    #[test]
+    fn tiles_out_of_the_map_are_rejected() {
+        let mut saved = saved_game();
+        saved.covered_tiles.push(Coordinates { x: 8, y: 0 });
+        let result = SavedGame::<TestState>::from_ron(&saved.to_ron().unwrap());
+        assert!(matches!(result, Err(SaveError::Invalid(_))));
+
+        let mut saved = saved_game();
+        saved.marked_tiles.push(Coordinates { x: 0, y: 6 });
+        assert!(matches!(saved.game(), Err(SaveError::Invalid(_))));
+    }
+
+    #[test]
+    fn marked_tiles_must_be_covered() {
+        let mut saved = saved_game();
+        let uncovered = saved
+            .tile_map
+            .tiles()
+            .find(|c| !saved.covered_tiles.contains(c))
+            .unwrap();
+        saved.marked_tiles.push(uncovered);
+        let result = SavedGame::<TestState>::from_ron(&saved.to_ron().unwrap());
+        assert!(matches!(result, Err(SaveError::Invalid(_))));
+
+        let mut saved = saved_game();
+        saved.questioned_tiles.push(saved.marked_tiles[0]);
+        assert!(matches!(saved.game(), Err(SaveError::Invalid(_))));
+    }
+
+    #[test]
+    fn loaded_options_keep_the_application_states_and_rng() {
+        fn zero_rng(_seed: u64) -> Box<dyn rand::RngCore> {
+            Box::new(rand::rngs::mock::StepRng::new(0, 0))
+        }
+        let mut saved = saved_game();
+        saved.options.bomb_count = 3;
+        saved.options.end_state = TestState::Game;
+        let current = BoardOptions {
+            rng: Some(zero_rng as crate::resources::RngFactory),
+            ..options()
+        };
+
+        let options = saved.board_options(&current);
+        assert_eq!(options.bomb_count, 3);
+        assert_eq!(options.end_state, TestState::End);
+        assert!(options.rng.is_some());
+    }
+
+    #[test]
+    fn bombs_must_agree_with_generation() {
+        let mut saved = saved_game();
+        saved.generated = false;
+        assert!(matches!(saved.game(), Err(SaveError::Invalid(_))));
+    }
+}
@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Atlas cell drawing each tile state
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtlasCells {
+    /// Tile still covered
+    pub covered: usize,
+    /// Uncovered tile, below the counters and bombs
+    pub uncovered: usize,
+    pub flag: usize,
+    /// Question mark of the player, drawn as text if `None`
+    #[serde(default)]
+    pub question_mark: Option<usize>,
+    pub bomb: usize,
+    /// Uncovered bomb of a lost game
+    pub exploded_bomb: usize,
+    /// Flag without bomb of a lost game, drawn instead of the cover and the flag
+    pub wrong_flag: usize,
+    /// Counters from 1, larger counters are drawn as text
+    pub numbers: Vec<usize>,
+}
+
+impl AtlasCells {
+    /// Cell of a bombs counter, `None` if the atlas has no cell for it
+    pub fn number(&self, count: u16) -> Option<usize> {
+        self.numbers.get(count.checked_sub(1)? as usize).copied()
+    }
+
+    /// All the cells used by the atlas
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        [
+            self.covered,
+            self.uncovered,
+            self.flag,
+            self.bomb,
+            self.exploded_bomb,
+            self.wrong_flag,
+        ]
+        .into_iter()
+        .chain(self.question_mark)
+        .chain(self.numbers.iter().copied())
+    }
+}
+
+/// Texture atlas replacing the sprites and counters of the tiles, e.g. for pixel-art skins
+#[derive(Debug, Clone)]
+pub struct TileAtlas {
+    pub atlas: Handle<TextureAtlas>,
+    pub cells: AtlasCells,
+}
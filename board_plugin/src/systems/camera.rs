@@ -0,0 +1,128 @@
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::input::touchpad::TouchpadMagnify;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::bounds::transform_rect;
+use crate::components::BoardCamera;
+use crate::resources::InputAction;
+use crate::systems::input::ActionInput;
+use crate::Board;
+
+/// Scroll distance of a mouse wheel line, for the wheels reporting pixels
+const PIXELS_PER_LINE: f32 = 100.0;
+
+/// Drag of the board with the `InputAction::Pan` inputs
+#[derive(Debug, Default, Resource)]
+pub(crate) struct CameraDrag {
+    /// Cursor position when the pan input was pressed
+    start: Vec2,
+    /// Cursor position at the previous frame, `None` if the pan input is not held
+    last: Option<Vec2>,
+    /// The cursor moved beyond `BoardCamera::drag_threshold` since the pan input was pressed.
+    /// Kept until the next press, so that the released buttons don't act on a tile
+    pub dragging: bool,
+}
+
+/// Pans and zooms the cameras with `BoardCamera`, then keeps their view on the board:
+/// the center stays in the board bounds and the zoom goes from the whole board, or
+/// `BoardCamera::max_visible_tiles` tiles of a huge one, to `BoardCamera::min_visible_tiles` tiles
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn control_camera(
+    window_primary_query: Query<&Window, With<PrimaryWindow>>,
+    input: ActionInput,
+    board: Option<Res<Board>>,
+    mut drag: ResMut<CameraDrag>,
+    mut wheel_evr: EventReader<MouseWheel>,
+    mut magnify_evr: EventReader<TouchpadMagnify>,
+    mut cameras: Query<(&Camera, &mut Transform, &mut OrthographicProjection, &BoardCamera)>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let cursor_position = window_primary_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+
+    // lines of wheel scrolled up and pinch in both zoom in
+    let lines: f32 = wheel_evr
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        })
+        .sum();
+    let magnification: f32 = magnify_evr.read().map(|event| event.0).sum();
+
+    let Some(board) = board else {
+        return;
+    };
+    let Ok(board_transform) = transforms.get(board.entity) else {
+        return;
+    };
+    // the board in world space, it may have been moved since its spawn
+    let board_rect = transform_rect(
+        &board_transform.affine(),
+        Rect::from_corners(Vec2::ZERO, board.bounds.size),
+    );
+
+    if input.just_pressed(InputAction::Pan) {
+        drag.dragging = false;
+        drag.start = cursor_position.unwrap_or_default();
+        drag.last = cursor_position;
+    } else if !input.pressed(InputAction::Pan) {
+        drag.last = None;
+    }
+    let pan_delta = match (drag.last, cursor_position) {
+        (Some(last), Some(position)) => {
+            drag.last = Some(position);
+            position - last
+        }
+        _ => Vec2::ZERO,
+    };
+
+    for (camera, mut transform, mut projection, settings) in cameras.iter_mut() {
+        let Some(viewport_size) = camera.logical_viewport_size() else {
+            continue;
+        };
+        if viewport_size.min_element() <= 0.0 {
+            continue;
+        }
+
+        if let (Some(_), Some(position)) = (drag.last, cursor_position) {
+            if position.distance(drag.start) > settings.drag_threshold {
+                drag.dragging = true;
+            }
+        }
+        // the window y axis goes down
+        if drag.dragging {
+            let delta = Vec2::new(-pan_delta.x, pan_delta.y) * projection.scale;
+            transform.translation += delta.extend(0.0);
+        }
+
+        // the whole board, unless it spawns too many chunks
+        let board_scale = (board_rect.size() / viewport_size).max_element().max(1.0);
+        let chunks_scale =
+            settings.max_visible_tiles * board.tile_size / viewport_size.max_element();
+        let max_scale = board_scale.min(chunks_scale);
+        let min_scale = (settings.min_visible_tiles * board.tile_size / viewport_size.min_element())
+            .min(max_scale);
+        let mut factor = (1.0 - settings.zoom_speed).powf(lines);
+        if magnification > -1.0 {
+            factor /= 1.0 + magnification;
+        }
+        let scale = (projection.scale * factor).clamp(min_scale, max_scale);
+
+        // the point under the cursor stays in place
+        if let Some(position) = cursor_position.filter(|_| scale != projection.scale) {
+            let offset = (position - viewport_size / 2.0) * Vec2::new(1.0, -1.0);
+            let center = transform.translation.truncate();
+            let pointed = center + offset * projection.scale;
+            let center = pointed + (center - pointed) * scale / projection.scale;
+            transform.translation = center.extend(transform.translation.z);
+        }
+        projection.scale = scale;
+
+        let center = transform.translation.truncate().clamp(board_rect.min, board_rect.max);
+        transform.translation = center.extend(transform.translation.z);
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashSet;
+
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use minesweeper_core::{Game, Tile};
+
+use crate::bounds;
+use crate::components::{self, Chunk, Coordinates};
+use crate::resources::{BoardAssets, SpriteMaterial};
+use crate::tile_look::TileLook;
+use crate::{Board, TILE_BOMB_COUNTS, TILE_COVER_Z, TILE_Z};
+
+/// Pips of the counter glyphs, clockwise from the top left corner of the tile
+const GLYPH_RING: [(f32, f32); 8] = [
+    (-1.0, 1.0),
+    (0.0, 1.0),
+    (1.0, 1.0),
+    (1.0, 0.0),
+    (1.0, -1.0),
+    (0.0, -1.0),
+    (-1.0, -1.0),
+    (-1.0, 0.0),
+];
+
+/// Spawns the chunks in the views of the cameras and despawns the chunks which left
+/// them. The tiles of despawned chunks are only kept in the game
+pub(crate) fn update_chunks(
+    mut commands: Commands,
+    board: Option<ResMut<Board>>,
+    board_assets: Res<BoardAssets>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let Some(mut board) = board else {
+        return;
+    };
+    let board = &mut *board;
+    // not spawned yet, the chunks come with the next frame
+    let Ok(board_transform) = transforms.get(board.entity) else {
+        return;
+    };
+    let to_board = board_transform.affine().inverse();
+
+    let mut visible = HashSet::new();
+    for (camera, camera_transform) in cameras.iter() {
+        if !camera.is_active {
+            continue;
+        }
+        let Some(viewport_size) = camera.logical_viewport_size() else {
+            continue;
+        };
+        let corners = [Vec2::ZERO, viewport_size]
+            .map(|corner| camera.viewport_to_world_2d(camera_transform, corner));
+        let [Some(first), Some(second)] = corners else {
+            continue;
+        };
+        let view = bounds::transform_rect(&to_board, Rect::from_corners(first, second));
+        visible.extend(board.visible_chunks(view));
+    }
+
+    let hidden: Vec<Chunk> = board
+        .chunks
+        .keys()
+        .filter(|chunk| !visible.contains(*chunk))
+        .copied()
+        .collect();
+    for chunk in hidden {
+        if let Some(entity) = board.chunks.remove(&chunk) {
+            commands.entity(entity).despawn_recursive();
+        }
+        for coordinates in board.chunk_tiles(chunk) {
+            if let Some(index) = board.game.tile_map().index_of(coordinates) {
+                board.cover_entities[index] = None;
+            }
+        }
+    }
+
+    for chunk in visible {
+        if board.chunks.contains_key(&chunk) {
+            continue;
+        }
+        let tiles = board.chunk_tiles(chunk);
+        let entity = commands
+            .spawn((
+                Name::new(format!("Chunk ({}, {})", chunk.x, chunk.y)),
+                chunk,
+                SpatialBundle::default(),
+            ))
+            .with_children(|parent| {
+                spawn_tiles(
+                    parent,
+                    &board.game,
+                    &tiles,
+                    board.tile_size,
+                    board.tile_padding,
+                    &mut board.cover_entities,
+                    &board_assets,
+                );
+            })
+            .id();
+        commands.entity(board.entity).add_child(entity);
+        board.chunks.insert(chunk, entity);
+    }
+}
+
+/// Spawns `tiles` as they are in `game`, see `TileLook`: covers of covered tiles, flags,
+/// question marks and, if the bombs are placed, the bombs and counters
+fn spawn_tiles(
+    parent: &mut ChildBuilder,
+    game: &Game,
+    tiles: &[Coordinates],
+    tile_size: f32,
+    tile_padding: f32,
+    cover_entities: &mut [Option<Entity>],
+    board_assets: &BoardAssets,
+) {
+    let topology = game.tile_map().topology();
+    // hexagonal tiles are drawn with smaller square sprites
+    let sprite_size = tile_size * topology.sprite_size();
+    // remove duplicate of logic from original tutorial
+    let tile_real_size = sprite_size - tile_padding;
+    // distance between the opposite sides of the tiles and covers
+    let shape_size = tile_size - tile_padding;
+
+    for &coordinates in tiles {
+        let tile = &game.tile_map()[coordinates];
+        let look = TileLook::new(game, coordinates, board_assets);
+        let (center_x, center_y) = topology.tile_center(coordinates);
+
+        let mut commands = board_assets.spawn_tile_sprite(
+            parent,
+            &look.tile_material,
+            |cells| Some(cells.uncovered),
+            topology,
+            shape_size,
+            Transform::from_xyz(center_x * tile_size, center_y * tile_size, TILE_Z),
+        );
+
+        commands
+            .insert(Name::new(format!("Tile: ({}, {})", coordinates.x, coordinates.y)))
+            .insert(coordinates);
+
+        if look.covered {
+            commands.with_children(|parent| {
+                let mut cover = board_assets.spawn_tile_sprite(
+                    parent,
+                    &look.cover_material,
+                    |cells| look.cover_cell(cells),
+                    topology,
+                    shape_size,
+                    Transform::from_xyz(0.0, 0.0, TILE_COVER_Z),
+                );
+                cover.insert(Name::new("Tile Cover"));
+                if let Some(mark) = look.mark {
+                    cover.with_children(|parent| mark(parent, sprite_size, board_assets));
+                }
+                if let Some(index) = game.tile_map().index_of(coordinates) {
+                    cover_entities[index] = Some(cover.id());
+                }
+            });
+        }
+
+        if look.content {
+            spawn_tile_content(&mut commands, tile, look.exploded, tile_real_size, board_assets);
+        }
+    }
+}
+
+/// Adds the bomb or the bombs counter of `tile` to the tile entity.
+/// An `exploded` bomb is drawn with the exploded cell of the atlas
+pub(crate) fn spawn_tile_content(
+    commands: &mut EntityCommands,
+    tile: &Tile,
+    exploded: bool,
+    tile_real_size: f32,
+    board_assets: &BoardAssets,
+) {
+    let transform = Transform::from_xyz(0., 0., TILE_BOMB_COUNTS);
+    match tile {
+        Tile::Bomb => {
+            commands.insert(components::Bomb);
+            commands.with_children(|parent| {
+                board_assets.spawn_sprite(
+                    parent,
+                    &board_assets.bomb_material,
+                    |cells| Some(if exploded { cells.exploded_bomb } else { cells.bomb }),
+                    tile_real_size,
+                    transform,
+                );
+            });
+        }
+        Tile::BombNeighbour(bombs_count) => {
+            let count = *bombs_count;
+            commands.insert(components::BombNeighbor { count });
+            commands.with_children(|parent| {
+                if board_assets.has_number_cell(count) {
+                    board_assets.spawn_sprite(
+                        parent,
+                        &SpriteMaterial::default(),
+                        |cells| cells.number(count),
+                        tile_real_size,
+                        transform,
+                    );
+                } else if board_assets.counter_glyphs && count as usize <= GLYPH_RING.len() {
+                    // a smaller digit in a ring of pips, one per bomb
+                    parent.spawn(bomb_count_text_bundle(
+                        count,
+                        tile_real_size * 0.6,
+                        board_assets,
+                    ));
+                    let color = board_assets.bomb_counter_color(count);
+                    let pip_size = tile_real_size * 0.12;
+                    for (x, y) in GLYPH_RING.iter().take(count as usize) {
+                        parent.spawn(SpriteBundle {
+                            sprite: Sprite {
+                                color,
+                                custom_size: Some(Vec2::splat(pip_size)),
+                                ..Default::default()
+                            },
+                            transform: Transform::from_xyz(
+                                x * tile_real_size * 0.36,
+                                y * tile_real_size * 0.36,
+                                TILE_BOMB_COUNTS,
+                            ),
+                            ..Default::default()
+                        });
+                    }
+                } else {
+                    parent.spawn(bomb_count_text_bundle(
+                        count,
+                        tile_real_size,
+                        board_assets
+                    ));
+                }
+            });
+        }
+        Tile::Empty => (),
+    }
+}
+
+fn bomb_count_text_bundle(count: u16, font_size: f32, board_assets: &BoardAssets) -> Text2dBundle {
+    let color = board_assets.bomb_counter_color(count);
+    // counters of large neighbourhoods have several digits to fit in the tile
+    let text = count.to_string();
+    let font_size = font_size * (1.2 / text.len() as f32).min(1.0);
+
+    let style = TextStyle {
+        font: board_assets.bomb_counter_font.clone(),
+        font_size,
+        color,
+    };
+    // adopted 0.9 to 0.10 and simplified API
+    let text =
+        Text::from_section(text, style).with_alignment(TextAlignment::Center);
+
+    Text2dBundle {
+        text,
+        // z-order, print text on top of the tile
+        transform: Transform::from_xyz(0.0, 0.0, TILE_BOMB_COUNTS),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::Instant;
+use minesweeper_core::solver::Solver;
+use minesweeper_core::Topology;
+
+use crate::components::CursorHighlight;
+use crate::events::{TileChordEvent, TileMarkEvent, TileTriggerEvent};
+use crate::resources::{BoardCursor, InputAction};
+use crate::systems::input::ActionInput;
+use crate::systems::save::CurrentOptions;
+use crate::{Board, Coordinates};
+
+/// Time given to the solver to find a hint, the pair deductions are slow on huge boards
+const HINT_DURATION: Duration = Duration::from_millis(50);
+
+/// Cursor actions with their step. Row 0 is the bottom row
+const MOVE_ACTIONS: [(InputAction, (i8, i8)); 4] = [
+    (InputAction::CursorUp, (0, 1)),
+    (InputAction::CursorDown, (0, -1)),
+    (InputAction::CursorLeft, (-1, 0)),
+    (InputAction::CursorRight, (1, 0)),
+];
+
+/// Cursor highlight in the shape of the tiles whose opposite sides are `size` apart,
+/// hidden until the keyboard or a gamepad is used
+pub(crate) fn spawn_cursor(parent: &mut ChildBuilder, topology: Topology, size: f32) {
+    let (width, height) = topology.tile_extent();
+    parent.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1.0, 0.9, 0.0, 0.4),
+                custom_size: Some(Vec2::new(width, height) * size),
+                ..Default::default()
+            },
+            texture: crate::tile_shape::texture(topology).unwrap_or_default(),
+            visibility: Visibility::Hidden,
+            transform: Transform::from_xyz(0.0, 0.0, crate::TILE_CURSOR_Z),
+            ..Default::default()
+        },
+        Name::new("Cursor"),
+        CursorHighlight,
+    ));
+}
+
+/// Direction held by the player and the time of its next repeated move
+#[derive(Default)]
+pub(crate) struct CursorRepeat {
+    direction: (i8, i8),
+    held: Duration,
+    next_move: Duration,
+}
+
+/// Moves the cursor with the bound keys and buttons, arrows, WASD, vi keys and d-pad by
+/// default, or with the left stick. A held direction repeats after `InputBindings::repeat_delay`
+pub(crate) fn move_cursor<T: States>(
+    input: ActionInput,
+    time: Res<Time>,
+    board: Res<Board>,
+    board_options: CurrentOptions<T>,
+    mut cursor: ResMut<BoardCursor>,
+    mut repeat: Local<CursorRepeat>,
+) {
+    let stick = input.left_stick_direction();
+    let direction = MOVE_ACTIONS
+        .iter()
+        .filter(|(action, _)| input.pressed_without_mouse(*action))
+        .fold(stick, |sum, (_, delta)| (sum.0 + delta.0, sum.1 + delta.1));
+    let direction = (direction.0.signum(), direction.1.signum());
+
+    if direction == (0, 0) {
+        *repeat = CursorRepeat::default();
+        return;
+    }
+    if direction != repeat.direction {
+        *repeat = CursorRepeat {
+            direction,
+            held: Duration::ZERO,
+            next_move: input.bindings().repeat_delay,
+        };
+    } else {
+        repeat.held += time.delta();
+        if repeat.held < repeat.next_move {
+            return;
+        }
+        repeat.next_move += input.bindings().repeat_interval;
+    }
+
+    // the first move only shows the cursor
+    if cursor.visible {
+        cursor.coordinates = board_options.cursor_movement.step(
+            cursor.coordinates,
+            direction,
+            board.game.tile_map(),
+        );
+    }
+    cursor.visible = true;
+}
+
+/// Reveals, flags or chords the tile under the cursor, like the mouse buttons do
+pub(crate) fn cursor_actions(
+    input: ActionInput,
+    board: Res<Board>,
+    mut cursor: ResMut<BoardCursor>,
+    mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
+    mut tile_mark_ewr: EventWriter<TileMarkEvent>,
+    mut tile_chord_ewr: EventWriter<TileChordEvent>,
+) {
+    // the cursor of a previous board may be outside of a smaller one or on a void cell
+    let tile_map = board.game.tile_map();
+    if !tile_map.is_tile(cursor.coordinates) {
+        cursor.coordinates = tile_map.tiles().next().unwrap_or_default();
+    }
+    let coordinates = cursor.coordinates;
+
+    if input.just_pressed_without_mouse(InputAction::Reveal) {
+        cursor.visible = true;
+        log::info!("Trying uncover tile on {}", coordinates);
+        tile_trigger_ewr.send(TileTriggerEvent { coordinates });
+    }
+    if input.just_pressed_without_mouse(InputAction::Flag) {
+        cursor.visible = true;
+        log::info!("Trying mark tile on {}", coordinates);
+        tile_mark_ewr.send(TileMarkEvent { coordinates });
+    }
+    if input.just_pressed_without_mouse(InputAction::Chord) {
+        cursor.visible = true;
+        log::info!("Trying chord on {}", coordinates);
+        tile_chord_ewr.send(TileChordEvent { coordinates });
+    }
+}
+
+/// Moves the cursor to the nearest tile which the solver proves safe. The flags of the
+/// player are ignored, they may be wrong. The search gives up after `HINT_DURATION`
+pub(crate) fn show_hint(input: ActionInput, board: Res<Board>, mut cursor: ResMut<BoardCursor>) {
+    if !input.just_pressed(InputAction::Hint) {
+        return;
+    }
+    // every tile of a board without bombs yet is safe
+    if !board.game.is_generated() {
+        return;
+    }
+
+    let tile_map = board.game.tile_map();
+    let mut solver = Solver::with_mask(tile_map, board.game.covered_tiles(), [])
+        .with_deadline(Instant::now() + HINT_DURATION);
+    let from = cursor.coordinates;
+    let distance = |c: &Coordinates| from.x.abs_diff(c.x) as u32 + from.y.abs_diff(c.y) as u32;
+    match solver.safe_tiles().into_iter().min_by_key(distance) {
+        Some(coordinates) => {
+            log::info!("Hint: {} is safe", coordinates);
+            cursor.coordinates = coordinates;
+            cursor.visible = true;
+        }
+        None => log::info!("Hint: no tile is safe without guessing"),
+    }
+}
+
+/// Places the highlight on the cursor tile
+pub(crate) fn update_cursor_highlight(
+    board: Option<Res<Board>>,
+    cursor: Res<BoardCursor>,
+    mut highlight_query: Query<(&mut Transform, &mut Visibility), With<CursorHighlight>>,
+) {
+    let Some(board) = board else {
+        return;
+    };
+    if !cursor.is_changed() && !board.is_changed() {
+        return;
+    }
+
+    for (mut transform, mut visibility) in highlight_query.iter_mut() {
+        let position = board.tile_position(cursor.coordinates);
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        *visibility = if cursor.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+use crate::components::{HudBombCounter, HudRoot, HudTimer};
+use crate::resources::{BoardAssets, Hud};
+use crate::systems::save::CurrentOptions;
+use crate::Board;
+
+/// HUD texts along the top of the window: the timer on the left and the bombs counter
+/// on the right. They are UI nodes, the camera zoom and pan don't move them
+fn spawn_hud(commands: &mut Commands, tile_size: f32, font: &Handle<Font>) {
+    let style = TextStyle {
+        font: font.clone(),
+        font_size: tile_size,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::SpaceBetween,
+                    padding: UiRect::axes(Val::Px(tile_size / 2.0), Val::Px(tile_size / 4.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Name::new("HUD"),
+            HudRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("000", style.clone()),
+                Name::new("HUD timer"),
+                HudTimer,
+            ));
+            parent.spawn((
+                TextBundle::from_section("000", style),
+                Name::new("HUD bomb counter"),
+                HudBombCounter,
+            ));
+        });
+}
+
+/// Spawns the HUD with the board and removes it without a board, then updates its texts
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn update_hud<T: States>(
+    mut commands: Commands,
+    board: Option<Res<Board>>,
+    board_options: CurrentOptions<T>,
+    board_assets: Option<Res<BoardAssets>>,
+    mut hud: ResMut<Hud>,
+    root_query: Query<Entity, With<HudRoot>>,
+    mut timer_query: Query<&mut Text, (With<HudTimer>, Without<HudBombCounter>)>,
+    mut counter_query: Query<&mut Text, (With<HudBombCounter>, Without<HudTimer>)>,
+) {
+    let (Some(board), Some(board_assets)) = (board, board_assets) else {
+        for entity in root_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    };
+    if root_query.is_empty() {
+        spawn_hud(&mut commands, board.tile_size, &board_assets.menu_font);
+        return;
+    }
+
+    // bombs of a pending board are not placed yet
+    let bomb_count = if board.game.is_generated() {
+        board.game.tile_map().bomb_count()
+    } else {
+        board_options.bomb_count
+    };
+    let values = Hud {
+        elapsed: board.stats.elapsed,
+        remaining_bombs: bomb_count as i32 - board.game.flagged_count() as i32,
+    };
+    if hud.elapsed != values.elapsed || hud.remaining_bombs != values.remaining_bombs {
+        *hud = values;
+    }
+
+    // a changed text is laid out again
+    let timer = format!("{:03}", hud.elapsed.as_secs());
+    for mut text in timer_query.iter_mut() {
+        if text.sections[0].value != timer {
+            text.sections[0].value = timer.clone();
+        }
+    }
+    let counter = format!("{:03}", hud.remaining_bombs);
+    for mut text in counter_query.iter_mut() {
+        if text.sections[0].value != counter {
+            text.sections[0].value = counter.clone();
+        }
+    }
+    // the board is resized with the window and restyled with the theme
+    for mut text in timer_query.iter_mut().chain(counter_query.iter_mut()) {
+        if text.sections[0].style.font_size != board.tile_size {
+            text.sections[0].style.font_size = board.tile_size;
+        }
+        if board_assets.is_changed() {
+            text.sections[0].style.font = board_assets.menu_font.clone();
+        }
+    }
+}
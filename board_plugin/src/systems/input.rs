@@ -1,6 +1,11 @@
-use crate::events::{TileMarkEvent, TileTriggerEvent};
-use crate::Board;
+use std::collections::HashSet;
 
+use crate::events::{TileChordEvent, TileMarkEvent, TileTriggerEvent};
+use crate::resources::{BoardCursor, InputAction, InputBinding, InputBindings};
+use crate::systems::camera::CameraDrag;
+use crate::{Board, Coordinates};
+
+use bevy::ecs::system::SystemParam;
 use bevy::input::mouse::MouseButtonInput;
 use bevy::input::ButtonState;
 use bevy::log;
@@ -8,41 +13,181 @@ use bevy::log;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 
+/// Buttons state of the inputs bound to the actions, see `InputBindings`. Also reads
+/// the actions handled by the application, e.g. `InputAction::Save`
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    bindings: Res<'w, InputBindings>,
+    keys: Res<'w, Input<KeyCode>>,
+    mouse_buttons: Res<'w, Input<MouseButton>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+    gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl ActionInput<'_> {
+    /// An input bound to `action` was released this frame
+    pub fn just_released(&self, action: InputAction) -> bool {
+        self.any(action, true, Input::just_released, Input::just_released, Input::just_released)
+    }
+
+    /// An input bound to `action` is held
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.any(action, true, Input::pressed, Input::pressed, Input::pressed)
+    }
+
+    /// An input bound to `action` was pressed this frame
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.any(action, true, Input::just_pressed, Input::just_pressed, Input::just_pressed)
+    }
+
+    /// A key or gamepad button bound to `action` is held
+    pub fn pressed_without_mouse(&self, action: InputAction) -> bool {
+        self.any(action, false, Input::pressed, Input::pressed, Input::pressed)
+    }
+
+    /// A key or gamepad button bound to `action` was pressed this frame. Mouse buttons
+    /// are excluded, they act on the tile under the mouse and not under the cursor
+    pub fn just_pressed_without_mouse(&self, action: InputAction) -> bool {
+        self.any(action, false, Input::just_pressed, Input::just_pressed, Input::just_pressed)
+    }
+
+    /// Left stick of the gamepads tilted beyond `InputBindings::stick_threshold`,
+    /// as steps from -1 to 1 on each axis
+    pub fn left_stick_direction(&self) -> (i8, i8) {
+        let threshold = self.bindings.stick_threshold;
+        let mut direction = (0, 0);
+        for gamepad in self.gamepads.iter() {
+            let axis = |axis_type| {
+                let value = self
+                    .gamepad_axes
+                    .get(GamepadAxis::new(gamepad, axis_type))
+                    .unwrap_or(0.0);
+                (value >= threshold) as i8 - (value <= -threshold) as i8
+            };
+            direction.0 += axis(GamepadAxisType::LeftStickX);
+            direction.1 += axis(GamepadAxisType::LeftStickY);
+        }
+        direction
+    }
+
+    pub fn bindings(&self) -> &InputBindings {
+        &self.bindings
+    }
+
+    fn any(
+        &self,
+        action: InputAction,
+        with_mouse: bool,
+        key_check: fn(&Input<KeyCode>, KeyCode) -> bool,
+        mouse_check: fn(&Input<MouseButton>, MouseButton) -> bool,
+        gamepad_check: fn(&Input<GamepadButton>, GamepadButton) -> bool,
+    ) -> bool {
+        self.bindings.bindings(action).iter().any(|binding| match *binding {
+            InputBinding::Key(key) => key_check(&self.keys, key),
+            InputBinding::Mouse(button) => with_mouse && mouse_check(&self.mouse_buttons, button),
+            InputBinding::Gamepad(button_type) => self.gamepads.iter().any(|gamepad| {
+                gamepad_check(&self.gamepad_buttons, GamepadButton::new(gamepad, button_type))
+            }),
+        })
+    }
+}
+
+/// Tile under the window `position`, picked through the first active camera showing it
+fn pick_tile(
+    board: &Board,
+    board_transform: &GlobalTransform,
+    cameras: &Query<(&Camera, &GlobalTransform)>,
+    position: Vec2,
+) -> Option<Coordinates> {
+    cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .find_map(|(camera, camera_transform)| {
+            board.mouse_position(camera, camera_transform, board_transform, position)
+        })
+}
+
 // adopted 0.9 to 0.10 https://bevyengine.org/learn/migration-guides/0.9-0.10/#windows-as-entities
+#[allow(clippy::too_many_arguments)]
 pub fn input_handling(
     window_primary_query: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    transforms: Query<&GlobalTransform>,
     board: Res<Board>,
+    bindings: Res<InputBindings>,
+    drag: Res<CameraDrag>,
+    mut cursor: ResMut<BoardCursor>,
+    buttons: Res<Input<MouseButton>>,
+    mut deferred_buttons: Local<HashSet<MouseButton>>,
     mut button_evr: EventReader<MouseButtonInput>,
     mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
     mut tile_mark_ewr: EventWriter<TileMarkEvent>,
+    mut tile_chord_ewr: EventWriter<TileChordEvent>,
 ) {
     // pattern from bevy migration guide
     let Ok(window) = window_primary_query.get_single() else {
         return;
     };
+    let Ok(board_transform) = transforms.get(board.entity) else {
+        return;
+    };
 
     for event in button_evr.read() {
+        let binding = InputBinding::Mouse(event.button);
         // adopted 0.7 to 0.8 https://bevyengine.org/learn/migration-guides/0.7-0.8/#rename-elementstate-to-buttonstate
-        if let ButtonState::Pressed = event.state {
-            if let Some(click_position) = window.cursor_position() {
-                if let Some(tile_coordinates) = board.mouse_position(window, click_position) {
-                    match event.button {
-                        MouseButton::Left => {
-                            log::info!("Trying uncover tile on {}", tile_coordinates);
-                            tile_trigger_ewr.send(TileTriggerEvent {
-                                coordinates: tile_coordinates,
-                            });
-                        }
-                        MouseButton::Right => {
-                            log::info!("Trying mark tile on {}", tile_coordinates);
-                            tile_mark_ewr.send(TileMarkEvent {
-                                coordinates: tile_coordinates,
-                            })
-                        }
-                        _ => (),
-                    }
-                }
-            };
+        match event.state {
+            // the press may start a drag of the board, the button acts on its release
+            ButtonState::Pressed if bindings.is_bound(InputAction::Pan, binding) => {
+                deferred_buttons.insert(event.button);
+                continue;
+            }
+            ButtonState::Pressed => (),
+            ButtonState::Released if deferred_buttons.remove(&event.button) && !drag.dragging => (),
+            ButtonState::Released => continue,
+        }
+
+        let Some(click_position) = window.cursor_position() else {
+            continue;
+        };
+        let Some(tile_coordinates) = pick_tile(&board, board_transform, &cameras, click_position) else {
+            continue;
+        };
+        // the keyboard continues from the clicked tile
+        cursor.coordinates = tile_coordinates;
+        cursor.visible = false;
+
+        let is_reveal = bindings.is_bound(InputAction::Reveal, binding);
+        let is_flag = bindings.is_bound(InputAction::Flag, binding);
+        let held = |action| {
+            bindings
+                .mouse_buttons(action)
+                .any(|b| b != event.button && buttons.pressed(b))
+        };
+        // reveal and flag buttons pressed together make a chord
+        let chord = bindings.is_bound(InputAction::Chord, binding)
+            || (is_reveal && held(InputAction::Flag))
+            || (is_flag && held(InputAction::Reveal));
+        if chord {
+            log::info!("Trying chord on {}", tile_coordinates);
+            tile_chord_ewr.send(TileChordEvent {
+                coordinates: tile_coordinates,
+            });
+            // the other button of the chord doesn't act on its release
+            deferred_buttons.clear();
+            continue;
+        }
+
+        if is_reveal {
+            log::info!("Trying uncover tile on {}", tile_coordinates);
+            tile_trigger_ewr.send(TileTriggerEvent {
+                coordinates: tile_coordinates,
+            });
+        } else if is_flag {
+            log::info!("Trying mark tile on {}", tile_coordinates);
+            tile_mark_ewr.send(TileMarkEvent {
+                coordinates: tile_coordinates,
+            })
         }
     }
 }
@@ -1,45 +1,104 @@
-use bevy::{prelude::*, log, transform::commands, ecs::query};
-use crate::{Board, BoardAssets, events::TileMarkEvent, resources::board::ToggleMarkResult};
+use bevy::prelude::*;
+use crate::{Board, BoardAssets, resources::board::ToggleMarkResult};
+use crate::events::{FlagPlacedEvent, FlagRemovedEvent, TileMarkEvent};
 
+/// Spawns a mark as a child of the tile cover, see `spawn_flag`
+pub(crate) type SpawnMark = fn(&mut ChildBuilder, f32, &BoardAssets);
 
 pub fn mark_tiles(
     mut commands: Commands,
     mut board: ResMut<Board>,
     board_assests: Res<BoardAssets>,
     mut tile_mark_event_rdr: EventReader<TileMarkEvent>,
+    mut flag_placed_event_wr: EventWriter<FlagPlacedEvent>,
+    mut flag_removed_event_wr: EventWriter<FlagRemovedEvent>,
     query: Query<&Children>
 ) {
     for event in tile_mark_event_rdr.read() {
-        match board.try_toggle_mark(&event.coordinates) {
-            ToggleMarkResult::FlagIsSet(entity) =>{
-                commands.entity(entity).with_children(|parent| {
-                    parent.spawn(SpriteBundle {
-                        texture: board_assests.flag_material.texture.clone(),
-                        sprite: Sprite {
-                            custom_size: Some(Vec2::splat(board.tile_size)),
-                            color: board_assests.flag_material.color,
-                            ..Default::default()
-                        },
-                        transform: Transform::from_xyz(0.0, 0.0, crate::TILE_FLAG_Z),
-                        ..Default::default()
-                    })
-                    .insert(Name::new("Flag"));
-                });
-            },
-            ToggleMarkResult::FlagIsUnset(entity) => {
-                let children = match query.get(entity) {
-                    Ok(value) => value,
-                    Err(e) => {
-                        log::error!("Failed to retrieve flag entity components: {}", e);
-                        continue;
-                    },
-                };
-
-                for child in children {
-                    commands.entity(*child).despawn_recursive();
+        board.game.start_move();
+        // tiles of despawned chunks get their mark when the chunk is spawned
+        let (entity, mark): (_, Option<SpawnMark>) =
+            match board.try_toggle_mark(&event.coordinates) {
+                ToggleMarkResult::FlagIsSet(entity) => {
+                    flag_placed_event_wr.send(FlagPlacedEvent {
+                        coordinates: event.coordinates,
+                    });
+                    (entity, Some(spawn_flag))
+                }
+                ToggleMarkResult::FlagIsUnset(entity) => {
+                    flag_removed_event_wr.send(FlagRemovedEvent {
+                        coordinates: event.coordinates,
+                    });
+                    (entity, None)
+                }
+                ToggleMarkResult::QuestionIsSet(entity) => {
+                    flag_removed_event_wr.send(FlagRemovedEvent {
+                        coordinates: event.coordinates,
+                    });
+                    (entity, Some(spawn_question_mark))
                 }
-            },
-            ToggleMarkResult::DidNothing => (),
+                ToggleMarkResult::QuestionIsUnset(entity) => (entity, None),
+                ToggleMarkResult::DidNothing => continue,
+            };
+        board.count_click(|clicks| clicks.flag += 1);
+
+        let Some(entity) = entity else {
+            continue;
+        };
+        // the previous mark, a cover without mark has no children
+        for child in query.get(entity).into_iter().flatten() {
+            commands.entity(*child).despawn_recursive();
+        }
+        if let Some(mark) = mark {
+            commands.entity(entity).with_children(|parent| {
+                mark(parent, board.sprite_size(), &board_assests);
+            });
         }
     }
-}
\ No newline at end of file
+}
+
+/// Flag sprite, spawned as a child of the tile cover
+pub(crate) fn spawn_flag(parent: &mut ChildBuilder, tile_size: f32, board_assets: &BoardAssets) {
+    board_assets
+        .spawn_sprite(
+            parent,
+            &board_assets.flag_material,
+            |cells| Some(cells.flag),
+            tile_size,
+            Transform::from_xyz(0.0, 0.0, crate::TILE_FLAG_Z),
+        )
+        .insert(Name::new("Flag"));
+}
+
+/// Question mark, spawned as a child of the tile cover. Drawn with the question mark
+/// cell of the atlas or, without it, as text with the counter font
+pub(crate) fn spawn_question_mark(
+    parent: &mut ChildBuilder,
+    tile_size: f32,
+    board_assets: &BoardAssets,
+) {
+    let transform = Transform::from_xyz(0.0, 0.0, crate::TILE_FLAG_Z);
+    let cell = board_assets.atlas.as_ref().and_then(|atlas| atlas.cells.question_mark);
+    let mut question_mark = match cell {
+        Some(cell) => board_assets.spawn_sprite(
+            parent,
+            &board_assets.flag_material,
+            |_| Some(cell),
+            tile_size,
+            transform,
+        ),
+        None => {
+            let style = TextStyle {
+                font: board_assets.bomb_counter_font.clone(),
+                font_size: tile_size,
+                color: board_assets.question_mark_color,
+            };
+            parent.spawn(Text2dBundle {
+                text: Text::from_section("?", style).with_alignment(TextAlignment::Center),
+                transform,
+                ..Default::default()
+            })
+        }
+    };
+    question_mark.insert(Name::new("Question mark"));
+}
@@ -1,4 +1,12 @@
 pub(crate) mod input;
+pub(crate) mod cursor;
+pub(crate) mod camera;
 pub(crate) mod uncover;
 pub(crate) mod mark;
+pub(crate) mod stats;
+pub(crate) mod hud;
+pub(crate) mod undo;
 
+pub(crate) mod chunks;
+pub(crate) mod theme;
+pub(crate) mod save;
@@ -0,0 +1,133 @@
+use std::ops::Deref;
+
+use bevy::ecs::system::SystemParam;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use minesweeper_core::GameState;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::components::{EndCover, PauseCover};
+use crate::events::{BoardCreatedEvent, BoardDestroyedEvent, LoadGameEvent, SaveGameEvent};
+use crate::resources::{BoardAssets, BoardOptions, InputBindings, SavedGame};
+use crate::{Board, BoardPlugin};
+
+/// Options of a board restored by `load_game`. The application `BoardOptions` is kept
+/// for the next boards, this resource is removed when a new board is created
+#[derive(Resource)]
+pub(crate) struct LoadedOptions<T: States>(pub BoardOptions<T>);
+
+/// Options of the current board: the ones of a loaded game, else the application ones
+#[derive(SystemParam)]
+pub(crate) struct CurrentOptions<'w, T: States> {
+    options: Res<'w, BoardOptions<T>>,
+    loaded: Option<Res<'w, LoadedOptions<T>>>,
+}
+
+impl<T: States> Deref for CurrentOptions<'_, T> {
+    type Target = BoardOptions<T>;
+
+    fn deref(&self) -> &Self::Target {
+        match &self.loaded {
+            Some(loaded) => &loaded.0,
+            None => &self.options,
+        }
+    }
+}
+
+pub(crate) fn save_game<T: States + Serialize + DeserializeOwned>(
+    mut save_game_evr: EventReader<SaveGameEvent>,
+    board: Option<Res<Board>>,
+    board_options: CurrentOptions<T>,
+) {
+    for event in save_game_evr.read() {
+        let Some(board) = &board else {
+            log::warn!("No board to save");
+            continue;
+        };
+        match SavedGame::new(board, &board_options).save(&event.path) {
+            Ok(()) => log::info!("Game is saved to {}", event.path.display()),
+            Err(e) => log::error!("Failed to save game to {}: {}", event.path.display(), e),
+        }
+    }
+}
+
+/// Replaces the current board with a saved one and resumes the game.
+/// A finished game is shown with its result box in the end state. The options of the
+/// save only apply to this board, see `LoadedOptions`
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn load_game<T: States + Serialize + DeserializeOwned>(
+    mut commands: Commands,
+    mut load_game_evr: EventReader<LoadGameEvent>,
+    board: Option<Res<Board>>,
+    board_options: Res<BoardOptions<T>>,
+    board_assets: Res<BoardAssets>,
+    bindings: Res<InputBindings>,
+    state: Res<State<T>>,
+    mut next_state: ResMut<NextState<T>>,
+    window_primary_query: Query<&Window, With<PrimaryWindow>>,
+    cover_query: Query<Entity, Or<(With<PauseCover>, With<EndCover>)>>,
+    mut board_events: (EventWriter<BoardDestroyedEvent>, EventWriter<BoardCreatedEvent>),
+) {
+    // only the last request matters
+    let Some(event) = load_game_evr.read().last() else {
+        return;
+    };
+    let loaded = SavedGame::<T>::load(&event.path)
+        .and_then(|saved| saved.game().map(|game| (saved, game)));
+    let (saved, game) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            log::error!("Failed to load game from {}: {}", event.path.display(), e);
+            return;
+        }
+    };
+    log::info!("Load game from {}", event.path.display());
+
+    if let Some(board) = board {
+        commands.entity(board.entity).despawn_recursive();
+        board_events.0.send(BoardDestroyedEvent {
+            entity: board.entity,
+        });
+    }
+    for entity in cover_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let options = saved.board_options(&board_options);
+    let window_size = BoardPlugin::<T>::window_size(&window_primary_query);
+    let tile_size = options.tile_size_px(window_size, game.tile_map());
+    let mut board = BoardPlugin::<T>::spawn_board(
+        &mut commands,
+        game,
+        saved.seed,
+        tile_size,
+        &options,
+        &board_assets,
+    );
+    board.stats = saved.stats;
+
+    board_events.1.send(BoardCreatedEvent {
+        entity: board.entity,
+        seed: board.seed,
+    });
+    let next = match board.game.state() {
+        GameState::Playing => options.game_state.clone(),
+        state => {
+            BoardPlugin::<T>::spawn_end_cover(
+                &mut commands,
+                state == GameState::Lost,
+                &board,
+                &board_assets,
+                &bindings,
+            );
+            options.end_state.clone()
+        }
+    };
+    if *state.get() != next {
+        next_state.set(next);
+    }
+    commands.insert_resource(board);
+    commands.insert_resource(LoadedOptions(options));
+}
@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+use minesweeper_core::GameState;
+
+use crate::Board;
+
+/// Counts the game time from the first reveal of the player until the game is over.
+/// Tiles uncovered by the safe start don't start it, clicks which uncover nothing are not
+/// counted. Runs only in the game state, so pauses are not counted
+pub fn track_time(time: Res<Time>, mut board: ResMut<Board>) {
+    let clicks = board.stats.clicks;
+    if clicks.reveal + clicks.chord > 0 && board.game.state() == GameState::Playing {
+        board.stats.elapsed += time.delta();
+    }
+}
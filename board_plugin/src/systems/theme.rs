@@ -0,0 +1,207 @@
+use bevy::asset::{LoadState, RecursiveDependencyLoadState};
+use bevy::log;
+use bevy::prelude::*;
+
+use crate::components::reveal_wave::RevealWave;
+use crate::components::{Chunk, Coordinates, CursorHighlight, EndCover, PauseCover};
+use crate::resources::{ActiveTheme, BoardAssets, BoardTheme};
+use crate::systems::chunks::spawn_tile_content;
+use crate::systems::save::CurrentOptions;
+use crate::tile_look::TileLook;
+use crate::Board;
+
+/// Replaces `BoardAssets` with the active theme when it's loaded, modified or switched.
+/// The counter palette and glyphs of the current assets are kept.
+/// A theme which fails to load is reported once, the current assets are kept or,
+/// without assets yet, the built-in `BoardAssets::default` is used
+pub(crate) fn apply_theme(
+    mut commands: Commands,
+    active_theme: Option<Res<ActiveTheme>>,
+    themes: Res<Assets<BoardTheme>>,
+    asset_server: Res<AssetServer>,
+    mut theme_evr: EventReader<AssetEvent<BoardTheme>>,
+    current_assets: Option<Res<BoardAssets>>,
+    mut failed_theme: Local<Option<AssetId<BoardTheme>>>,
+) {
+    let Some(active_theme) = active_theme else {
+        theme_evr.clear();
+        return;
+    };
+    let id = active_theme.0.id();
+    let failed = asset_server.get_load_state(id) == Some(LoadState::Failed);
+    let dependency_failed = asset_server.get_recursive_dependency_load_state(id)
+        == Some(RecursiveDependencyLoadState::Failed);
+    // a texture or font of the theme failed, it's applied without them
+    let mut theme_changed = false;
+    if !failed && !dependency_failed {
+        // reported again if it fails after a fix on disk
+        if *failed_theme == Some(id) {
+            *failed_theme = None;
+        }
+    } else if *failed_theme != Some(id) {
+        *failed_theme = Some(id);
+        log::error!("Failed to load theme {:?}", active_theme.0.path());
+        theme_changed = dependency_failed && !failed;
+    }
+    if failed {
+        if current_assets.is_none() {
+            log::info!("Apply the built-in theme");
+            commands.insert_resource(BoardAssets::default());
+        }
+        return;
+    }
+
+    theme_changed |= theme_evr
+        .read()
+        .any(|event| event.is_loaded_with_dependencies(id) || event.is_modified(id));
+    if !theme_changed && !active_theme.is_changed() {
+        return;
+    }
+    // a switched theme is applied once loaded
+    let Some(theme) = themes.get(id) else {
+        return;
+    };
+    let mut board_assets = theme.assets.clone();
+    log::info!("Apply theme {}", board_assets.label);
+    if let Some(current_assets) = current_assets {
+        board_assets.counter_palette = current_assets.counter_palette;
+        board_assets.counter_glyphs = current_assets.counter_glyphs;
+    }
+    commands.insert_resource(board_assets);
+}
+
+/// Restyles the board and its pause or end box when `BoardAssets` is changed,
+/// e.g. by another theme or palette. The game goes on: the entities are kept with
+/// their reveal waves, only their sprites, texts and tile contents are replaced
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub(crate) fn restyle_board<T: States>(
+    mut commands: Commands,
+    board_assets: Option<Res<BoardAssets>>,
+    board: Option<ResMut<Board>>,
+    board_options: CurrentOptions<T>,
+    children_query: Query<&Children>,
+    tiles: Query<&Coordinates, Without<Chunk>>,
+    waves: Query<(), With<RevealWave>>,
+    mut sprites: Query<(&mut Sprite, Option<&mut Handle<Image>>, Has<CursorHighlight>), Without<Coordinates>>,
+    mut cover_query: Query<(&mut BackgroundColor, &Children, Has<PauseCover>), Or<(With<PauseCover>, With<EndCover>)>>,
+    mut texts: Query<&mut Text>,
+) {
+    let Some(board_assets) = board_assets.filter(|assets| assets.is_changed()) else {
+        return;
+    };
+
+    if let Some(mut board) = board {
+        let board = &mut *board;
+        board.tile_padding = board_assets.tile_padding.unwrap_or(board_options.tile_padding);
+        let topology = board.game.tile_map().topology();
+        let shape_size = board.tile_size - board.tile_padding;
+
+        // the background and the cursor
+        for &child in children_query.get(board.entity).into_iter().flatten() {
+            let Ok((mut sprite, texture, cursor)) = sprites.get_mut(child) else {
+                continue;
+            };
+            if cursor {
+                let (width, height) = topology.tile_extent();
+                sprite.custom_size = Some(Vec2::new(width, height) * shape_size);
+            } else if let Some(mut texture) = texture {
+                let material = board_assets.contrasted(&board_assets.board_material, Color::WHITE);
+                sprite.color = material.color;
+                *texture = material.texture;
+            }
+        }
+
+        for &chunk in board.chunks.values() {
+            for &tile in children_query.get(chunk).into_iter().flatten() {
+                let Ok(&coordinates) = tiles.get(tile) else {
+                    continue;
+                };
+                let (covers, contents): (Vec<Entity>, Vec<Entity>) = children_query
+                    .get(tile)
+                    .into_iter()
+                    .flatten()
+                    .partition(|&&child| {
+                        waves.contains(child) || board.cover_entities.contains(&Some(child))
+                    });
+                restyle_tile(
+                    &mut commands,
+                    board,
+                    &board_assets,
+                    tile,
+                    coordinates,
+                    &covers,
+                    &contents,
+                );
+            }
+        }
+    }
+    for (mut background, children, paused) in cover_query.iter_mut() {
+        background.0 = if paused {
+            board_assets.pause_cover_color
+        } else {
+            board_assets.end_cover_color
+        };
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            for section in text.sections.iter_mut() {
+                section.style.color = board_assets.cover_text_color;
+                section.style.font = board_assets.menu_font.clone();
+            }
+        }
+    }
+}
+
+/// Draws a spawned tile with `board_assets`, see `TileLook`: the tile and its `covers`
+/// are restyled in place, the marks and the bomb or counter `contents` are spawned again
+fn restyle_tile(
+    commands: &mut Commands,
+    board: &Board,
+    board_assets: &BoardAssets,
+    tile_entity: Entity,
+    coordinates: Coordinates,
+    covers: &[Entity],
+    contents: &[Entity],
+) {
+    let topology = board.game.tile_map().topology();
+    let sprite_size = board.sprite_size();
+    let shape_size = board.tile_size - board.tile_padding;
+    let look = TileLook::new(&board.game, coordinates, board_assets);
+
+    board_assets.restyle_tile_sprite(
+        &mut commands.entity(tile_entity),
+        &look.tile_material,
+        |cells| Some(cells.uncovered),
+        topology,
+        shape_size,
+    );
+
+    for &cover in covers {
+        let mut cover = commands.entity(cover);
+        board_assets.restyle_tile_sprite(
+            &mut cover,
+            &look.cover_material,
+            |cells| look.cover_cell(cells),
+            topology,
+            shape_size,
+        );
+        cover.despawn_descendants();
+        if let Some(mark) = look.mark {
+            cover.with_children(|parent| mark(parent, sprite_size, board_assets));
+        }
+    }
+
+    for &content in contents {
+        commands.entity(content).despawn_recursive();
+    }
+    if look.content {
+        let tile = &board.game.tile_map()[coordinates];
+        let tile_real_size = sprite_size - board.tile_padding;
+        spawn_tile_content(
+            &mut commands.entity(tile_entity),
+            tile,
+            look.exploded,
+            tile_real_size,
+            board_assets,
+        );
+    }
+}
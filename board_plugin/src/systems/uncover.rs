@@ -1,76 +1,143 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::log;
 
-use crate::events::BoardCompletedEvent;
-use crate::events::BombExplosionEvent;
+use crate::components::reveal_wave::RevealWave;
+use crate::events::{BoardCompletedEvent, BombExplosionEvent, TileRevealedEvent};
+use minesweeper_core::GameState;
 use crate::{
-    components::{bomb::Bomb, bomb_neighbor::BombNeighbor, uncover::Uncover, Coordinates},
-    events::TileTriggerEvent,
+    components::{uncover::Uncover, Coordinates},
+    events::{TileChordEvent, TileTriggerEvent},
     resources::board::Board,
 };
 
+/// Events sent when tiles are uncovered
+#[derive(SystemParam)]
+pub struct RevealEvents<'w> {
+    board_compeleted_event_wr: EventWriter<'w, BoardCompletedEvent>,
+    board_bomb_explosion_event_wr: EventWriter<'w, BombExplosionEvent>,
+    tile_revealed_event_wr: EventWriter<'w, TileRevealedEvent>,
+}
+
 pub fn trigger_event_handler(
     mut commands: Commands,
-    board: Res<Board>,
+    mut board: ResMut<Board>,
     mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+    mut reveal_events: RevealEvents,
 ) {
 
     // adopted
     for trigger_event in tile_trigger_evr.read() {
         log::info!("Tile trigger event handler {:?}", trigger_event);
-        if let Some(entity) = board.tile_to_uncover(&trigger_event.coordinates) {
-            log::info!("insert Uncover to {:?}",*entity);
-            commands.entity(*entity).insert(Uncover);
+        let was_playing = board.game.state() == GameState::Playing;
+        board.game.start_move();
+        // triggers on flagged or uncovered tiles are not counted
+        if reveal_tiles(&mut commands, &mut board, trigger_event.coordinates, &mut reveal_events) {
+            board.count_click(|clicks| clicks.reveal += 1);
         }
+        send_completed(&board, was_playing, &mut reveal_events);
     }
 }
 
-pub fn uncover_tiles(
+pub fn chord_event_handler(
     mut commands: Commands,
     mut board: ResMut<Board>,
-    children: Query<(Entity, &Parent), With<Uncover>>,
-    parents: Query<(&Coordinates, Option<&Bomb>, Option<&BombNeighbor>)>,    
-    mut board_compeleted_event_wr: EventWriter<BoardCompletedEvent>,
-    mut board_bomb_explosion_event_wr: EventWriter<BombExplosionEvent>
+    mut tile_chord_evr: EventReader<TileChordEvent>,
+    mut reveal_events: RevealEvents,
 ) {
-    for (entity, parent) in children.iter() {
-        commands.entity(entity).despawn_recursive();
-        // adopted parent.0 -> parent.get 
-        // https://bevyengine.org/learn/migration-guides/0.7-0.8/#hierarchy-commandization
+    for chord_event in tile_chord_evr.read() {
+        log::info!("Tile chord event handler {:?}", chord_event);
+        let was_playing = board.game.state() == GameState::Playing;
+        board.game.start_move();
+        let mut revealed = false;
+        for coordinates in board.game.chord_targets(chord_event.coordinates) {
+            revealed |= reveal_tiles(&mut commands, &mut board, coordinates, &mut reveal_events);
+        }
+        if revealed {
+            board.count_click(|clicks| clicks.chord += 1);
+        }
+        send_completed(&board, was_playing, &mut reveal_events);
+    }
+}
 
-        let (coordinates, bomb, bomb_counter) = match parents.get(parent.get()) {
-            Ok(v) => v,
-            Err(e) => {
-                log::error!{"{}" ,e};
-                continue;
-            }
-        }; 
+/// Uncovers the tiles requested by the entities with the `Uncover` component
+pub fn uncover_tiles(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    requests: Query<(Entity, &Coordinates), With<Uncover>>,
+    mut reveal_events: RevealEvents,
+) {
+    for (entity, coordinates) in requests.iter() {
+        commands.entity(entity).despawn();
+        let was_playing = board.game.state() == GameState::Playing;
+        reveal_tiles(&mut commands, &mut board, *coordinates, &mut reveal_events);
+        send_completed(&board, was_playing, &mut reveal_events);
+    }
+}
 
-        if board.is_completed() {
-            log::info!("Board is compeleted 🍾");
-            board_compeleted_event_wr.send(BoardCompletedEvent);
+/// Despawns the covers of an animated reveal when their wave comes
+pub fn animate_reveal(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut covers: Query<(Entity, &mut RevealWave)>,
+) {
+    for (entity, mut wave) in covers.iter_mut() {
+        if wave.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
         }
+    }
+}
+
+/// Uncovers a tile with its empty region in a single pass and removes the covers.
+/// Returns `false` if nothing was uncovered
+fn reveal_tiles(
+    commands: &mut Commands,
+    board: &mut Board,
+    coordinates: Coordinates,
+    reveal_events: &mut RevealEvents,
+) -> bool {
+    let revealed = board.reveal(coordinates);
+    if revealed.is_empty() {
+        log::info!("Tried to uncover an already uncovered tile");
+        return false;
+    }
 
-        match board.try_uncover_tile(coordinates) {
-            None => log::info!("Tried to uncover an already uncovered tile"),
-            Some(e) => { 
-                //log::info!("Uncovered tile {} (entity: {:?})",coordinates, e) 
-            },
+    for (tile, cover) in revealed {
+        // tiles of chunks which are not spawned have no cover to remove
+        match (cover, board.reveal_animation) {
+            (None, _) => (),
+            (Some(cover), Some(delay)) if tile.wave > 0 => {
+                commands.entity(cover).insert(RevealWave {
+                    timer: Timer::new(delay * tile.wave, TimerMode::Once),
+                });
+            }
+            (Some(cover), _) => commands.entity(cover).despawn_recursive(),
         }
 
-        if bomb.is_some() {
+        reveal_events.tile_revealed_event_wr.send(TileRevealedEvent {
+            coordinates: tile.coordinates,
+            tile: tile.tile,
+        });
+
+        if tile.tile.is_bomb() {
             log::info!("Boom 💥!");
-            board_bomb_explosion_event_wr.send(BombExplosionEvent);
-        }
-        // If the tile is empty (no bomb near tile)...
-        else if bomb_counter.is_none() {
-            // ..We propagate the unconverng by adding the 'Uncover' 
-            // which will then be removed next frame
-            for entity in board.adjancent_covered_tiles(*coordinates) {
-                commands.entity(entity).insert(Uncover);
-            };
+            reveal_events.board_bomb_explosion_event_wr.send(BombExplosionEvent {
+                coordinates: tile.coordinates,
+            });
         }
     }
 
-    
+    true
+}
+
+/// Sends the `BoardCompletedEvent` if the game was won since `was_playing`,
+/// once the click of the player is counted
+fn send_completed(board: &Board, was_playing: bool, reveal_events: &mut RevealEvents) {
+    if was_playing && board.game.state() == GameState::Won {
+        log::info!("Board is compeleted 🍾");
+        reveal_events.board_compeleted_event_wr.send(BoardCompletedEvent {
+            elapsed: board.stats.elapsed,
+            clicks: board.stats.clicks,
+        });
+    }
 }
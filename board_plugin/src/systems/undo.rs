@@ -0,0 +1,44 @@
+use bevy::log;
+use bevy::prelude::*;
+use minesweeper_core::GameState;
+
+use crate::components::EndCover;
+use crate::resources::{BoardOptions, InputAction};
+use crate::systems::input::ActionInput;
+use crate::Board;
+
+/// Cancels the last move of the player. The spawned chunks are drawn again from the game,
+/// an undone loss or win removes the result box and resumes the game
+pub(crate) fn undo_move<T: States>(
+    mut commands: Commands,
+    input: ActionInput,
+    board: Option<ResMut<Board>>,
+    board_options: Res<BoardOptions<T>>,
+    state: Res<State<T>>,
+    mut next_state: ResMut<NextState<T>>,
+    end_cover_query: Query<Entity, With<EndCover>>,
+) {
+    if !input.just_released(InputAction::Undo) {
+        return;
+    }
+    let Some(mut board) = board else {
+        return;
+    };
+    let Some(tiles) = board.game.undo() else {
+        log::info!("Nothing to undo");
+        return;
+    };
+    log::info!("Undo a move on {} tiles", tiles.len());
+    // every move which can be undone was counted
+    if let Some(clicks) = board.click_history.pop() {
+        board.stats.clicks = clicks;
+    }
+    board.despawn_chunks(&mut commands);
+
+    if *state.get() == board_options.end_state && board.game.state() == GameState::Playing {
+        for entity in end_cover_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        next_state.set(board_options.game_state.clone());
+    }
+}
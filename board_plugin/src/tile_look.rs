@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use minesweeper_core::{Coordinates, Game, GameState};
+
+use crate::resources::{AtlasCells, BoardAssets, SpriteMaterial};
+use crate::systems::mark::{spawn_flag, spawn_question_mark, SpawnMark};
+
+/// How a tile is drawn in the current state of its game, shared by the spawned chunks
+/// and the restyled tiles. A lost game shows its bombs and wrong flags
+pub(crate) struct TileLook {
+    /// Uncovered tile, below the counters and bombs
+    pub tile_material: SpriteMaterial,
+    /// The tile is drawn with a cover
+    pub covered: bool,
+    pub cover_material: SpriteMaterial,
+    /// Flag without bomb of a lost game
+    wrong_flag: bool,
+    /// Flag or question mark drawn on the cover
+    pub mark: Option<SpawnMark>,
+    /// The bombs are placed, the bomb or counter of the tile is drawn
+    pub content: bool,
+    /// Uncovered bomb of a lost game
+    pub exploded: bool,
+}
+
+impl TileLook {
+    pub fn new(game: &Game, coordinates: Coordinates, board_assets: &BoardAssets) -> Self {
+        let tile = game.tile_map()[coordinates];
+        let lost = game.state() == GameState::Lost;
+        let flagged = game.is_flagged(coordinates);
+        let covered = game.is_covered(coordinates);
+        let wrong_flag = lost && flagged && !tile.is_bomb();
+
+        let mut cover_material =
+            board_assets.contrasted(&board_assets.covered_tile_material, Color::rgb(0.7, 0.7, 0.7));
+        if wrong_flag {
+            let wrong_flag_material = SpriteMaterial::color(board_assets.wrong_flag_color);
+            cover_material.color =
+                board_assets.contrasted(&wrong_flag_material, Color::rgb(0.6, 0.0, 0.0)).color;
+        }
+        // the wrong flag cell of an atlas replaces the flag
+        let atlas_covers = board_assets.atlas_draws_tiles(game.tile_map().topology());
+        let mark: Option<SpawnMark> = if flagged && !(wrong_flag && atlas_covers) {
+            Some(spawn_flag)
+        } else if game.is_questioned(coordinates) {
+            Some(spawn_question_mark)
+        } else {
+            None
+        };
+
+        Self {
+            tile_material: board_assets.contrasted(&board_assets.tile_material, Color::BLACK),
+            // unflagged bombs of a lost game are shown
+            covered: covered && !(lost && tile.is_bomb() && !flagged),
+            cover_material,
+            wrong_flag,
+            mark,
+            content: game.is_generated(),
+            exploded: lost && !covered,
+        }
+    }
+
+    /// Atlas cell of the cover
+    pub fn cover_cell(&self, cells: &AtlasCells) -> Option<usize> {
+        Some(if self.wrong_flag {
+            cells.wrong_flag
+        } else {
+            cells.covered
+        })
+    }
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use minesweeper_core::{HexOrientation, Topology};
+
+/// White hexagon with a vertex on top, tinted by the sprite color
+const POINTY_HEX_TEXTURE: Handle<Image> = Handle::weak_from_u128(0x5c6f_1d2e_93a4_4b7e_8c10_2f6a_7e3b_9d01);
+/// White hexagon with a side on top, tinted by the sprite color
+const FLAT_HEX_TEXTURE: Handle<Image> = Handle::weak_from_u128(0x5c6f_1d2e_93a4_4b7e_8c10_2f6a_7e3b_9d02);
+
+/// Width of the hexagon textures between their parallel sides
+const HEX_TEXTURE_APOTHEM_PX: u32 = 64;
+/// Samples on each axis of a pixel, for smooth edges
+const SUBSAMPLES: u32 = 4;
+
+/// Texture giving its shape to a tile sprite, `None` for square tiles
+pub(crate) fn texture(topology: Topology) -> Option<Handle<Image>> {
+    match topology {
+        Topology::Square => None,
+        Topology::Hex(HexOrientation::PointyTop) => Some(POINTY_HEX_TEXTURE),
+        Topology::Hex(HexOrientation::FlatTop) => Some(FLAT_HEX_TEXTURE),
+    }
+}
+
+/// Adds the hexagon textures to the images
+pub(crate) fn setup_tile_shapes(mut images: ResMut<Assets<Image>>) {
+    for orientation in [HexOrientation::PointyTop, HexOrientation::FlatTop] {
+        let topology = Topology::Hex(orientation);
+        if let Some(handle) = texture(topology) {
+            images.insert(handle, shape_image(topology));
+        }
+    }
+}
+
+/// White tile shape on a transparent background, the alpha is the covered part of each pixel
+fn shape_image(topology: Topology) -> Image {
+    let unit = 2.0 * HEX_TEXTURE_APOTHEM_PX as f32;
+    let (width, height) = topology.tile_extent();
+    let (width_px, height_px) = ((width * unit).ceil() as u32, (height * unit).ceil() as u32);
+
+    let mut data = Vec::with_capacity((width_px * height_px * 4) as usize);
+    // rows of the texture go down
+    for row in (0..height_px).rev() {
+        for column in 0..width_px {
+            let inside = (0..SUBSAMPLES * SUBSAMPLES)
+                .filter(|sample| {
+                    let x = column as f32 + ((sample % SUBSAMPLES) as f32 + 0.5) / SUBSAMPLES as f32;
+                    let y = row as f32 + ((sample / SUBSAMPLES) as f32 + 0.5) / SUBSAMPLES as f32;
+                    let offset = ((x - width_px as f32 / 2.0) / unit, (y - height_px as f32 / 2.0) / unit);
+                    topology.is_inside_tile(offset, 0.5)
+                })
+                .count();
+            let alpha = (inside * 255 / (SUBSAMPLES * SUBSAMPLES) as usize) as u8;
+            data.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: width_px,
+            height: height_px,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
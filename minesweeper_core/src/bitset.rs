@@ -0,0 +1,191 @@
+/// Fixed size set of indices stored as bits, one bit per tile of a map
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "BitSetData"))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitSet {
+    len: usize,
+    /// Number of set bits, kept up to date by every change
+    count: usize,
+    words: Vec<u64>,
+}
+
+/// Deserialized fields of a [`BitSet`], checked before use
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "BitSet")]
+struct BitSetData {
+    len: usize,
+    count: usize,
+    words: Vec<u64>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BitSetData> for BitSet {
+    type Error = &'static str;
+
+    fn try_from(data: BitSetData) -> Result<Self, Self::Error> {
+        if data.words.len() != data.len.div_ceil(64) {
+            return Err("the bit set words don't match its length");
+        }
+        let tail = data.len % 64;
+        if tail != 0 && data.words.last().is_some_and(|last| last >> tail != 0) {
+            return Err("the bit set has bits past its length");
+        }
+        let count: usize = data.words.iter().map(|word| word.count_ones() as usize).sum();
+        if count != data.count {
+            return Err("the bit set count doesn't match its bits");
+        }
+        Ok(Self {
+            len: data.len,
+            count: data.count,
+            words: data.words,
+        })
+    }
+}
+
+impl BitSet {
+    /// Set of `len` indices, all unset
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            count: 0,
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    /// Set of `len` indices, all set
+    pub fn full(len: usize) -> Self {
+        let mut set = Self {
+            len,
+            count: len,
+            words: vec![u64::MAX; len.div_ceil(64)],
+        };
+        // bits past `len` stay unset so that counting is exact
+        if !len.is_multiple_of(64) {
+            if let Some(last) = set.words.last_mut() {
+                *last = (1 << (len % 64)) - 1;
+            }
+        }
+        set
+    }
+
+    /// Number of indices, set or not
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// `false` for indices out of the set
+    pub fn contains(&self, index: usize) -> bool {
+        if index >= self.len {
+            return false;
+        }
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Sets or unsets `index`. Returns `true` if the bit changed
+    pub fn set(&mut self, index: usize, value: bool) -> bool {
+        if index >= self.len {
+            return false;
+        }
+        let (word, mask) = (&mut self.words[index / 64], 1 << (index % 64));
+        let changed = (*word & mask != 0) != value;
+        if !changed {
+            return false;
+        }
+        if value {
+            *word |= mask;
+            self.count += 1;
+        } else {
+            *word &= !mask;
+            self.count -= 1;
+        }
+        true
+    }
+
+    /// Unsets all indices
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+        self.count = 0;
+    }
+
+    /// Number of set indices
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Set indices in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_count() {
+        let mut set = BitSet::new(100);
+        assert!(set.set(3, true));
+        assert!(!set.set(3, true));
+        assert!(set.set(64, true));
+        assert!(set.set(99, true));
+        assert_eq!(set.count(), 3);
+        assert!(set.contains(64));
+        assert!(set.set(64, false));
+        assert!(!set.contains(64));
+        assert_eq!(set.count(), 2);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 99]);
+
+        set.clear();
+        assert_eq!(set.count(), 0);
+        assert_eq!(set.iter().count(), 0);
+    }
+
+    #[test]
+    fn out_of_range_indices_are_ignored() {
+        let mut set = BitSet::new(10);
+        assert!(!set.set(10, true));
+        assert!(!set.contains(10));
+        assert!(!set.contains(usize::MAX));
+        assert_eq!(set.count(), 0);
+    }
+
+    #[test]
+    fn full_sets_stop_at_the_length() {
+        for len in [0, 1, 63, 64, 65, 128, 130] {
+            let set = BitSet::full(len);
+            assert_eq!(set.count(), len);
+            assert_eq!(set.iter().count(), len);
+            assert_eq!(set.iter().last(), len.checked_sub(1));
+            assert!(!set.contains(len));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn inconsistent_data_is_rejected() {
+        let data = |len, count, words: Vec<u64>| BitSetData { len, count, words };
+        assert!(BitSet::try_from(data(70, 2, vec![1, 1])).is_ok());
+        // missing word
+        assert!(BitSet::try_from(data(70, 1, vec![1])).is_err());
+        // bit 70 is past the length
+        assert!(BitSet::try_from(data(70, 1, vec![0, 1 << 6])).is_err());
+        // wrong count
+        assert!(BitSet::try_from(data(70, 3, vec![1, 1])).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, Sub};
+
+// Component is required by the board plugin, tiles entities are tagged by coordinates
+#[cfg_attr(feature = "bevy", derive(bevy_ecs::component::Component))]
+#[cfg_attr(feature = "bevy", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)] // lv - add derives on demand
+// todo
+pub struct Coordinates {
+    pub x: u16,
+    pub y: u16
+}
+
+impl Add for Coordinates {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y
+        }
+    }
+}
+
+impl Coordinates {
+    /// Moves by `delta` on a `width` x `height` board, `None` if it leaves the board
+    pub fn checked_offset(self, (dx, dy): (i8, i8), width: u16, height: u16) -> Option<Self> {
+        let x = self.x as i32 + dx as i32;
+        let y = self.y as i32 + dy as i32;
+        if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+            return None;
+        }
+        Some(Self {
+            x: x as u16,
+            y: y as u16,
+        })
+    }
+
+    /// Moves by `delta` on a `width` x `height` board whose edges are joined:
+    /// leaving a side comes back on the opposite side
+    pub fn wrapping_offset(self, (dx, dy): (i8, i8), width: u16, height: u16) -> Self {
+        let x = (self.x as i32 + dx as i32).rem_euclid(width.max(1) as i32);
+        let y = (self.y as i32 + dy as i32).rem_euclid(height.max(1) as i32);
+        Self {
+            x: x as u16,
+            y: y as u16,
+        }
+    }
+}
+
+impl Sub for Coordinates {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self{
+            x: self.x.saturating_sub(rhs.x),
+            y: self.y.saturating_sub(rhs.y)
+        }
+    }
+}
+
+impl Display for Coordinates {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "({},{})", self.x, self.y)
+    }
+}
\ No newline at end of file
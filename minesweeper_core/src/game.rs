@@ -0,0 +1,491 @@
+use std::collections::VecDeque;
+
+use crate::bitset::BitSet;
+use crate::coordinates::Coordinates;
+use crate::tile::Tile;
+use crate::tile_map::TileMap;
+
+/// Progress of a game
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GameState {
+    #[default]
+    Playing,
+    Won,
+    Lost,
+}
+
+/// Result of a flag toggle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagChange {
+    Set,
+    Unset,
+    /// The flag is replaced by a question mark, see [`Game::with_question_marks`]
+    Question,
+    /// The question mark is removed
+    Unquestion,
+    Nothing,
+}
+
+/// Tile uncovered by [`Game::reveal`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RevealedTile {
+    pub coordinates: Coordinates,
+    pub tile: Tile,
+    /// Distance to the revealed tile in the flood fill, 0 for the revealed tile itself
+    pub wave: u32,
+}
+
+/// Changes of a player move, see [`Game::start_move`]
+#[derive(Debug, Clone, Default)]
+struct Move {
+    /// State of the game before the move
+    state: GameState,
+    /// Tiles uncovered by the move, by index of the tile map
+    uncovered: Vec<usize>,
+    /// Tiles whose flag was toggled by the move, by index of the tile map
+    toggled: Vec<usize>,
+    /// Tiles whose question mark was toggled by the move, by index of the tile map
+    questioned: Vec<usize>,
+}
+
+impl Move {
+    fn is_empty(&self) -> bool {
+        self.uncovered.is_empty() && self.toggled.is_empty() && self.questioned.is_empty()
+    }
+}
+
+/// Rules of a game: which tiles are covered and flagged, and whether the game is won or lost
+#[derive(Debug, Clone, Default)]
+pub struct Game {
+    tile_map: TileMap,
+    /// Covered tiles by index of the tile map
+    covered: BitSet,
+    /// Flagged tiles by index of the tile map
+    flagged: BitSet,
+    /// Tiles marked with a question mark by index of the tile map
+    questioned: BitSet,
+    /// Does a flag toggle go through a question mark
+    question_marks: bool,
+    generated: bool,
+    state: GameState,
+    /// Moves of the player, the last one at the end
+    history: Vec<Move>,
+}
+
+impl Game {
+    /// New game on a generated map, all tiles are covered
+    pub fn new(tile_map: TileMap) -> Self {
+        let mut game = Self::pending(tile_map);
+        game.generated = true;
+        game
+    }
+
+    /// New game on a map without bombs, see [`Game::generate`]
+    pub fn pending(tile_map: TileMap) -> Self {
+        let mut covered = BitSet::full(tile_map.len());
+        for index in 0..tile_map.len() {
+            if tile_map.is_void(tile_map.coordinates_of(index)) {
+                covered.set(index, false);
+            }
+        }
+        Self {
+            flagged: BitSet::new(tile_map.len()),
+            questioned: BitSet::new(tile_map.len()),
+            question_marks: false,
+            tile_map,
+            covered,
+            generated: false,
+            state: GameState::Playing,
+            history: Vec::new(),
+        }
+    }
+
+    /// Game in progress, e.g. a loaded one. The state is deduced from the uncovered tiles,
+    /// the previous moves can't be undone
+    pub fn restore(
+        tile_map: TileMap,
+        generated: bool,
+        covered: impl IntoIterator<Item = Coordinates>,
+        flagged: impl IntoIterator<Item = Coordinates>,
+        questioned: impl IntoIterator<Item = Coordinates>,
+    ) -> Self {
+        let mut game = Self {
+            covered: BitSet::new(tile_map.len()),
+            flagged: BitSet::new(tile_map.len()),
+            questioned: BitSet::new(tile_map.len()),
+            question_marks: false,
+            tile_map,
+            generated,
+            state: GameState::Playing,
+            history: Vec::new(),
+        };
+        for coordinates in covered {
+            if let Some(index) = game.tile_index(coordinates) {
+                game.covered.set(index, true);
+            }
+        }
+        for coordinates in flagged {
+            if let Some(index) = game.tile_index(coordinates) {
+                game.flagged.set(index, true);
+            }
+        }
+        for coordinates in questioned {
+            if let Some(index) = game.tile_index(coordinates) {
+                game.questioned.set(index, true);
+            }
+        }
+        let exploded = game
+            .tile_map
+            .tiles()
+            .any(|c| game.tile_map.is_bomb_at(c) && !game.is_covered(c));
+        if exploded {
+            game.state = GameState::Lost;
+        } else if game.is_completed() {
+            game.state = GameState::Won;
+        }
+        game
+    }
+
+    /// Flag toggles go from a flag to a question mark before removing it. Question marks
+    /// are only a note of the player: they don't block uncovering and aren't counted as flags
+    pub fn with_question_marks(mut self, question_marks: bool) -> Self {
+        self.question_marks = question_marks;
+        self
+    }
+
+    /// Places the bombs with `generator`. Covered and flagged tiles are kept
+    pub fn generate<R>(&mut self, generator: impl FnOnce(&mut TileMap) -> R) -> R {
+        let result = generator(&mut self.tile_map);
+        self.generated = true;
+        result
+    }
+
+    pub fn is_generated(&self) -> bool {
+        self.generated
+    }
+
+    /// At least one tile is uncovered
+    pub fn is_started(&self) -> bool {
+        self.covered.count() < self.tile_map.tile_count()
+    }
+
+    pub fn tile_map(&self) -> &TileMap {
+        &self.tile_map
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn is_covered(&self, coordinates: Coordinates) -> bool {
+        self
+            .tile_index(coordinates)
+            .is_some_and(|index| self.covered.contains(index))
+    }
+
+    pub fn is_flagged(&self, coordinates: Coordinates) -> bool {
+        self
+            .tile_index(coordinates)
+            .is_some_and(|index| self.flagged.contains(index))
+    }
+
+    pub fn is_questioned(&self, coordinates: Coordinates) -> bool {
+        self
+            .tile_index(coordinates)
+            .is_some_and(|index| self.questioned.contains(index))
+    }
+
+    /// Tiles marked with a question mark, row by row from the bottom
+    pub fn questioned_tiles(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.questioned.iter().map(|index| self.tile_map.coordinates_of(index))
+    }
+
+    /// Covered tiles, row by row from the bottom
+    pub fn covered_tiles(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.covered.iter().map(|index| self.tile_map.coordinates_of(index))
+    }
+
+    pub fn covered_count(&self) -> usize {
+        self.covered.count()
+    }
+
+    /// Flagged tiles, row by row from the bottom
+    pub fn flagged_tiles(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.flagged.iter().map(|index| self.tile_map.coordinates_of(index))
+    }
+
+    pub fn flagged_count(&self) -> usize {
+        self.flagged.count()
+    }
+
+    /// All safe tiles are uncovered
+    pub fn is_completed(&self) -> bool {
+        self.generated && self.tile_map.bomb_count() as usize == self.covered.count()
+    }
+
+    /// Uncovers a single tile. Returns `None` if the tile can't be uncovered:
+    /// it's already uncovered, flagged or the game is over
+    pub fn uncover(&mut self, coordinates: Coordinates) -> Option<Tile> {
+        let index = self.tile_index(coordinates)?;
+        if self.state != GameState::Playing
+            || self.flagged.contains(index)
+            || !self.covered.set(index, false)
+        {
+            return None;
+        }
+
+        let questioned = self.questioned.set(index, false);
+        if let Some(last) = self.history.last_mut() {
+            last.uncovered.push(index);
+            if questioned {
+                last.questioned.push(index);
+            }
+        }
+        let tile = self.tile_map[coordinates];
+        if tile.is_bomb() {
+            self.state = GameState::Lost;
+        } else if self.is_completed() {
+            self.state = GameState::Won;
+        }
+        Some(tile)
+    }
+
+    /// Uncovers a tile and, if it's empty, the whole empty region around it.
+    /// Returns the uncovered tiles in the order of the flood fill
+    pub fn reveal(&mut self, coordinates: Coordinates) -> Vec<RevealedTile> {
+        let mut revealed = Vec::new();
+        // a tile is queued once, even if it neighbours many empty tiles
+        let mut queued = BitSet::new(self.tile_map.len());
+        if let Some(index) = self.tile_index(coordinates) {
+            queued.set(index, true);
+        }
+        let mut queue = VecDeque::from([(coordinates, 0)]);
+        while let Some((coordinates, wave)) = queue.pop_front() {
+            let Some(tile) = self.uncover(coordinates) else {
+                continue;
+            };
+            revealed.push(RevealedTile {
+                coordinates,
+                tile,
+                wave,
+            });
+            if tile == Tile::Empty {
+                for neighbour in self.adjacent_covered_tiles(coordinates) {
+                    let Some(index) = self.tile_index(neighbour) else {
+                        continue;
+                    };
+                    if !queued.contains(index) {
+                        queued.set(index, true);
+                        queue.push_back((neighbour, wave + 1));
+                    }
+                }
+            }
+        }
+        revealed
+    }
+
+    /// Neighbours to uncover when chording on `coordinates`: the tile must be an uncovered
+    /// counter with as many flagged neighbours as its count. Wrong flags make a bomb uncovered
+    pub fn chord_targets(&self, coordinates: Coordinates) -> Vec<Coordinates> {
+        if self.state != GameState::Playing
+            || !self.tile_map.is_tile(coordinates)
+            || self.is_covered(coordinates)
+        {
+            return Vec::new();
+        }
+        let Tile::BombNeighbour(count) = self.tile_map[coordinates] else {
+            return Vec::new();
+        };
+
+        let flags = self
+            .tile_map
+            .safe_square_at(coordinates)
+            .filter(|c| self.is_flagged(*c))
+            .count();
+        if flags != count as usize {
+            return Vec::new();
+        }
+        self.adjacent_covered_tiles(coordinates).collect()
+    }
+
+    /// Sets or removes a flag on a covered tile. With question marks, a flag is replaced
+    /// by a question mark which is removed by the next toggle
+    pub fn toggle_flag(&mut self, coordinates: Coordinates) -> FlagChange {
+        let Some(index) = self.tile_index(coordinates) else {
+            return FlagChange::Nothing;
+        };
+        if self.state != GameState::Playing || !self.covered.contains(index) {
+            return FlagChange::Nothing;
+        }
+        let (change, toggled, questioned) = if self.questioned.contains(index) {
+            (FlagChange::Unquestion, false, true)
+        } else if !self.flagged.contains(index) {
+            (FlagChange::Set, true, false)
+        } else if self.question_marks {
+            (FlagChange::Question, true, true)
+        } else {
+            (FlagChange::Unset, true, false)
+        };
+        if toggled {
+            self.toggle(index, false);
+        }
+        if questioned {
+            self.toggle(index, true);
+        }
+        if let Some(last) = self.history.last_mut() {
+            if toggled {
+                last.toggled.push(index);
+            }
+            if questioned {
+                last.questioned.push(index);
+            }
+        }
+        change
+    }
+
+    /// Starts a player move: the tiles uncovered and the flags toggled until the next move
+    /// are undone together by [`Game::undo`]. Changes before the first move, e.g. the
+    /// safe start, can't be undone
+    pub fn start_move(&mut self) {
+        // a move which changed nothing is not kept
+        if self.history.last().is_some_and(Move::is_empty) {
+            self.history.pop();
+        }
+        self.history.push(Move {
+            state: self.state,
+            ..Default::default()
+        });
+    }
+
+    /// Cancels the last move which changed the game, a lost or won game is played again.
+    /// Returns the tiles whose cover or mark is restored, `None` if there is no move to undo
+    pub fn undo(&mut self) -> Option<Vec<Coordinates>> {
+        let last = loop {
+            let last = self.history.pop()?;
+            if !last.is_empty() {
+                break last;
+            }
+        };
+        for &index in &last.uncovered {
+            self.covered.set(index, true);
+        }
+        for &index in &last.toggled {
+            self.toggle(index, false);
+        }
+        for &index in &last.questioned {
+            self.toggle(index, true);
+        }
+        self.state = last.state;
+        Some(
+            last.uncovered
+                .iter()
+                .chain(&last.toggled)
+                .chain(&last.questioned)
+                .map(|index| self.tile_map.coordinates_of(*index))
+                .collect(),
+        )
+    }
+
+    /// Covered and not flagged neighbours of `coordinates`
+    pub fn adjacent_covered_tiles(
+        &self,
+        coordinates: Coordinates,
+    ) -> impl Iterator<Item = Coordinates> + '_ {
+        self.tile_map
+            .safe_square_at(coordinates)
+            .filter(|c| self.is_covered(*c) && !self.is_flagged(*c))
+    }
+
+    /// Sets or unsets the flag, or the question mark, of a tile
+    fn toggle(&mut self, index: usize, question_mark: bool) {
+        let marks = if question_mark {
+            &mut self.questioned
+        } else {
+            &mut self.flagged
+        };
+        let marked = marks.contains(index);
+        marks.set(index, !marked);
+    }
+
+    /// Index of a tile in the tile map, `None` for void cells and out of bounds
+    fn tile_index(&self, coordinates: Coordinates) -> Option<usize> {
+        if self.tile_map.is_void(coordinates) {
+            return None;
+        }
+        self.tile_map.index_of(coordinates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn c(x: u16, y: u16) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    #[test]
+    fn undo_covers_the_region_of_a_move() {
+        // . . 1 B
+        let mut game = Game::new(TileMap::empty(4, 1).with_bombs(&[c(3, 0)]));
+        game.start_move();
+        game.toggle_flag(c(3, 0));
+        game.start_move();
+        assert_eq!(game.reveal(c(0, 0)).len(), 3);
+        assert_eq!(game.state(), GameState::Won);
+
+        let mut covered = game.undo().unwrap();
+        covered.sort_by_key(|c| c.x);
+        assert_eq!(covered, vec![c(0, 0), c(1, 0), c(2, 0)]);
+        assert_eq!(game.state(), GameState::Playing);
+        assert_eq!(game.covered_count(), 4);
+        assert!(game.is_flagged(c(3, 0)));
+
+        assert_eq!(game.undo(), Some(vec![c(3, 0)]));
+        assert!(!game.is_flagged(c(3, 0)));
+        assert_eq!(game.undo(), None);
+    }
+
+    #[test]
+    fn question_marks_follow_the_flags() {
+        let mut game =
+            Game::new(TileMap::empty(4, 1).with_bombs(&[c(3, 0)])).with_question_marks(true);
+        game.start_move();
+        assert_eq!(game.toggle_flag(c(0, 0)), FlagChange::Set);
+        game.start_move();
+        assert_eq!(game.toggle_flag(c(0, 0)), FlagChange::Question);
+        assert!(!game.is_flagged(c(0, 0)));
+        assert!(game.is_questioned(c(0, 0)));
+
+        // a question mark doesn't block the flood fill and is removed by it
+        game.start_move();
+        game.reveal(c(1, 0));
+        assert!(!game.is_covered(c(0, 0)));
+        assert!(!game.is_questioned(c(0, 0)));
+
+        game.undo();
+        assert!(game.is_questioned(c(0, 0)));
+        game.start_move();
+        assert_eq!(game.toggle_flag(c(0, 0)), FlagChange::Unquestion);
+        assert_eq!(game.questioned_tiles().count(), 0);
+        game.undo();
+        game.undo();
+        assert!(game.is_flagged(c(0, 0)));
+        assert!(!game.is_questioned(c(0, 0)));
+    }
+
+    #[test]
+    fn undo_skips_moves_without_changes() {
+        let mut game = Game::new(TileMap::empty(4, 1).with_bombs(&[c(3, 0)]));
+        game.start_move();
+        game.reveal(c(3, 0));
+        assert_eq!(game.state(), GameState::Lost);
+        // nothing can be uncovered in a lost game
+        game.start_move();
+        game.reveal(c(0, 0));
+
+        assert_eq!(game.undo(), Some(vec![c(3, 0)]));
+        assert_eq!(game.state(), GameState::Playing);
+        assert!(game.is_covered(c(3, 0)));
+    }
+}
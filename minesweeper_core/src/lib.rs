@@ -0,0 +1,21 @@
+//! Rules of the minesweeper without any engine: map generation, uncovering, flags and
+//! win/loss detection. The board plugin maps this model to Bevy entities.
+
+pub mod bitset;
+pub mod coordinates;
+pub mod game;
+pub mod mask;
+pub mod neighbourhood;
+pub mod solver;
+pub mod tile;
+pub mod tile_map;
+pub mod topology;
+
+pub use bitset::BitSet;
+pub use coordinates::Coordinates;
+pub use game::{FlagChange, Game, GameState, RevealedTile};
+pub use mask::{Mask, MaskError};
+pub use neighbourhood::Neighbourhood;
+pub use tile::Tile;
+pub use tile_map::{GenerationError, TileMap};
+pub use topology::{HexOrientation, Topology};
@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+
+use crate::coordinates::Coordinates;
+
+/// Failure to build a mask from a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskError {
+    /// The template has no tile
+    Empty,
+    /// The template is wider or higher than `u16::MAX` cells
+    TooLarge,
+}
+
+impl Display for MaskError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MaskError::Empty => write!(f, "the board mask has no tile"),
+            MaskError::TooLarge => write!(f, "the board mask is too large"),
+        }
+    }
+}
+
+impl std::error::Error for MaskError {}
+
+/// Shape of a board: which cells of its `width` x `height` rectangle are tiles.
+/// Void cells are neither tiles nor neighbours, see [`crate::TileMap::with_mask`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mask {
+    width: u16,
+    height: u16,
+    void: HashSet<Coordinates>,
+}
+
+impl Mask {
+    /// Rectangle without void cells
+    pub fn full(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            void: HashSet::new(),
+        }
+    }
+
+    /// Mask with the cells for which `is_tile` is true
+    pub fn from_fn(width: u16, height: u16, is_tile: impl Fn(Coordinates) -> bool) -> Self {
+        let void = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Coordinates { x, y }))
+            .filter(|c| !is_tile(*c))
+            .collect();
+        Self {
+            width,
+            height,
+            void,
+        }
+    }
+
+    /// Mask drawn with characters, one line per row and the first line on top.
+    /// `.` and spaces are void cells, any other character is a tile. Shorter lines
+    /// end with void cells
+    pub fn from_ascii(template: &str) -> Result<Self, MaskError> {
+        let lines: Vec<&str> = template
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .skip_while(|line| line.trim().is_empty())
+            .collect();
+        let height = lines
+            .iter()
+            .rposition(|line| !line.trim().is_empty())
+            .map_or(0, |last| last + 1);
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        if height == 0 {
+            return Err(MaskError::Empty);
+        }
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(MaskError::TooLarge);
+        }
+
+        let (width, height) = (width as u16, height as u16);
+        let mask = Self::from_fn(width, height, |c| {
+            let line = lines[(height - 1 - c.y) as usize];
+            line
+                .chars()
+                .nth(c.x as usize)
+                .is_some_and(|cell| cell != '.' && cell != ' ')
+        });
+        if mask.void.len() == width as usize * height as usize {
+            return Err(MaskError::Empty);
+        }
+        Ok(mask)
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Cells which are not tiles
+    pub fn void_cells(&self) -> &HashSet<Coordinates> {
+        &self.void
+    }
+
+    pub fn is_tile(&self, coordinates: Coordinates) -> bool {
+        coordinates.x < self.width
+            && coordinates.y < self.height
+            && !self.void.contains(&coordinates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile_map::{GenerationError, TileMap};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::time::Duration;
+
+    const fn c(x: u16, y: u16) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    #[test]
+    fn parses_ascii_templates() {
+        let mask = Mask::from_ascii("\n.#.\n###\n#\n\n").unwrap();
+        assert_eq!((mask.width(), mask.height()), (3, 3));
+        // the first line is the top row
+        assert!(!mask.is_tile(c(0, 2)));
+        assert!(mask.is_tile(c(1, 2)));
+        assert!(mask.is_tile(c(2, 1)));
+        assert!(mask.is_tile(c(0, 0)));
+        // short line
+        assert!(!mask.is_tile(c(1, 0)));
+        assert!(!mask.is_tile(c(3, 0)));
+        assert_eq!(mask.void_cells().len(), 4);
+
+        assert_eq!(Mask::from_ascii("x x\r\n xx\r\n").unwrap().void_cells().len(), 2);
+    }
+
+    #[test]
+    fn rejects_templates_without_tiles() {
+        assert_eq!(Mask::from_ascii(""), Err(MaskError::Empty));
+        assert_eq!(Mask::from_ascii("\n  \n"), Err(MaskError::Empty));
+        assert_eq!(Mask::from_ascii("..\n. "), Err(MaskError::Empty));
+    }
+
+    #[test]
+    fn void_cells_are_not_tiles_nor_neighbours() {
+        let mask = Mask::from_ascii("###\n#.#\n###").unwrap();
+        let mut tile_map = TileMap::empty(3, 3).with_mask(&mask);
+        assert_eq!(tile_map.tile_count(), 8);
+        assert!(tile_map.is_void(c(1, 1)));
+        assert!(!tile_map.tiles().any(|t| t == c(1, 1)));
+        assert!(!tile_map.safe_square_at(c(0, 0)).any(|t| t == c(1, 1)));
+        assert_eq!(tile_map.safe_square_at(c(0, 0)).count(), 2);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let void_start = tile_map.set_bombs_no_guess(1, &mut rng, c(1, 1), 10, Duration::from_secs(1));
+        assert_eq!(void_start, Err(GenerationError::InvalidStart(c(1, 1))));
+
+        tile_map.set_bombs(8, &mut rng);
+        assert!(!tile_map.is_bomb_at(c(1, 1)));
+        assert_eq!(tile_map.tiles().filter(|t| tile_map.is_bomb_at(*t)).count(), 8);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::coordinates::Coordinates;
+use crate::topology::Topology;
+
+// Delta coordinates of the 4 orthogonal neighbours
+const CROSS_COORDINATES: [(i8, i8); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+// Delta coordinates of the 8 knight moves
+const KNIGHT_COORDINATES: [(i8, i8); 8] = [
+    (-1, -2),
+    (1, -2),
+    (-2, -1),
+    (2, -1),
+    (-2, 1),
+    (2, 1),
+    (-1, 2),
+    (1, 2),
+];
+
+/// Largest radius of [`Neighbourhood::Radius`], with 224 neighbours
+pub const MAX_RADIUS: u8 = 7;
+
+/// Tiles whose bombs are counted by a tile. The counted tiles are also the ones
+/// uncovered around an empty tile and by a chord
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// Adjacent tiles of the topology: 8 squares or 6 hexagons
+    #[default]
+    Adjacent,
+    /// The 4 orthogonal squares (von Neumann neighbourhood)
+    Cross,
+    /// The 8 squares a chess knight moves to
+    Knight,
+    /// Square of tiles up to `radius` tiles away, capped at [`MAX_RADIUS`]
+    Radius(u8),
+    /// Any offsets as (column, row)
+    Custom(Vec<(i8, i8)>),
+}
+
+impl Neighbourhood {
+    /// Offsets of the neighbours of `coordinates`. Rules other than `Adjacent` are meant
+    /// for square tiles and use hexagonal offset coordinates as they are
+    pub fn deltas(
+        &self,
+        topology: Topology,
+        coordinates: Coordinates,
+    ) -> impl Iterator<Item = (i8, i8)> + '_ {
+        let (listed, radius): (&[(i8, i8)], i8) = match self {
+            Neighbourhood::Adjacent => (topology.neighbour_deltas(coordinates), 0),
+            Neighbourhood::Cross => (&CROSS_COORDINATES, 0),
+            Neighbourhood::Knight => (&KNIGHT_COORDINATES, 0),
+            Neighbourhood::Radius(radius) => (&[], (*radius).min(MAX_RADIUS) as i8),
+            Neighbourhood::Custom(deltas) => (deltas, 0),
+        };
+        // the square of a radius is generated, a radius of 0 gives no offset
+        let square = (-radius..=radius)
+            .flat_map(move |dy| (-radius..=radius).map(move |dx| (dx, dy)))
+            .filter(|delta| *delta != (0, 0));
+        listed.iter().copied().chain(square)
+    }
+
+    /// Largest distance of an offset on an axis
+    pub fn reach(&self) -> u16 {
+        match self {
+            Neighbourhood::Adjacent | Neighbourhood::Cross => 1,
+            Neighbourhood::Knight => 2,
+            Neighbourhood::Radius(radius) => (*radius).min(MAX_RADIUS) as u16,
+            Neighbourhood::Custom(deltas) => deltas
+                .iter()
+                .map(|(dx, dy)| dx.unsigned_abs().max(dy.unsigned_abs()) as u16)
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
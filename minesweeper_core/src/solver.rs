@@ -0,0 +1,344 @@
+use std::collections::{HashMap, HashSet};
+
+use instant::Instant;
+
+use crate::bitset::BitSet;
+use crate::coordinates::Coordinates;
+use crate::tile::Tile;
+use crate::tile_map::TileMap;
+
+/// A bombs constraint given by an uncovered counter: `bombs` of the `unknown` tiles are bombs
+struct Constraint {
+    /// Sorted tile indices
+    unknown: Vec<usize>,
+    bombs: usize,
+}
+
+impl Constraint {
+    /// Unknown tiles of `other` which are not unknown tiles of `self`, `None` if `self` has
+    /// a tile which is not in `other`. Both lists are sorted, they are merged
+    fn rest_of(&self, other: &Constraint) -> Option<Vec<usize>> {
+        let mut rest = Vec::new();
+        let mut tiles = self.unknown.iter().peekable();
+        for idx in &other.unknown {
+            match tiles.peek() {
+                Some(tile) if *tile < idx => return None,
+                Some(tile) if *tile == idx => {
+                    tiles.next();
+                }
+                _ => rest.push(*idx),
+            }
+        }
+        tiles.peek().is_none().then_some(rest)
+    }
+}
+
+/// Logical solver. Plays the board like a player who never guesses: only uncovered counters,
+/// flags and the total bombs count are used to deduce safe tiles and bombs.
+pub struct Solver<'a> {
+    tile_map: &'a TileMap,
+    covered: BitSet,
+    flagged: BitSet,
+    /// Time after which no more deductions are tried, see [`Solver::with_deadline`]
+    deadline: Option<Instant>,
+}
+
+impl<'a> Solver<'a> {
+    /// Solver for a fresh board, all tiles are covered and nothing is flagged
+    pub fn new(tile_map: &'a TileMap) -> Self {
+        Self::with_mask(tile_map, tile_map.tiles(), [])
+    }
+
+    /// Solver for a board in progress
+    pub fn with_mask(
+        tile_map: &'a TileMap,
+        covered: impl IntoIterator<Item = Coordinates>,
+        flagged: impl IntoIterator<Item = Coordinates>,
+    ) -> Self {
+        let mut solver = Self {
+            tile_map,
+            covered: BitSet::new(tile_map.len()),
+            flagged: BitSet::new(tile_map.len()),
+            deadline: None,
+        };
+        for coordinates in covered {
+            if let Some(idx) = solver.index(coordinates) {
+                solver.covered.set(idx, true);
+            }
+        }
+        for coordinates in flagged {
+            if let Some(idx) = solver.index(coordinates) {
+                solver.flagged.set(idx, true);
+            }
+        }
+        solver
+    }
+
+    /// Stops deducing at `deadline`, the board is then reported as not solved
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// The deadline is over, see [`Solver::with_deadline`]
+    pub fn is_timed_out(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    pub fn is_covered(&self, coordinates: Coordinates) -> bool {
+        self.index(coordinates).is_some_and(|idx| self.covered.contains(idx))
+    }
+
+    pub fn is_flagged(&self, coordinates: Coordinates) -> bool {
+        self.index(coordinates).is_some_and(|idx| self.flagged.contains(idx))
+    }
+
+    /// All bombs are flagged or all safe tiles are uncovered
+    pub fn is_solved(&self) -> bool {
+        self.covered.count() == self.tile_map.bomb_count() as usize
+    }
+
+    /// Uncovers a tile, empty tiles uncover their neighbours. Returns `false` on a bomb
+    pub fn uncover(&mut self, coordinates: Coordinates) -> bool {
+        let Some(idx) = self.index(coordinates) else {
+            return true;
+        };
+        if self.tile_map.is_bomb_at(coordinates) {
+            return false;
+        }
+
+        let mut queue = vec![idx];
+        while let Some(idx) = queue.pop() {
+            if !self.covered.set(idx, false) {
+                continue;
+            }
+            self.flagged.set(idx, false);
+
+            let coordinates = self.coordinates(idx);
+            if self.tile(coordinates) == Tile::Empty {
+                queue.extend(self.neighbours(coordinates).filter(|n| self.covered.contains(*n)));
+            }
+        }
+        true
+    }
+
+    /// Applies every deduction available on the current state.
+    /// Returns `false` if nothing new could be deduced
+    pub fn step(&mut self) -> bool {
+        let constraints = self.constraints();
+        let mut safe: HashSet<usize> = HashSet::new();
+        let mut bombs: HashSet<usize> = HashSet::new();
+
+        // single counter: all unknown neighbours are safe or all are bombs
+        for constraint in &constraints {
+            if constraint.bombs == 0 {
+                safe.extend(&constraint.unknown);
+            } else if constraint.bombs == constraint.unknown.len() {
+                bombs.extend(&constraint.unknown);
+            }
+        }
+
+        // pair of counters: unknown tiles of `a` are a subset of unknown tiles of `b`
+        if safe.is_empty() && bombs.is_empty() {
+            // constraints by unknown tile, `b` shares the first tile of `a`
+            let mut by_tile: HashMap<usize, Vec<&Constraint>> = HashMap::new();
+            for constraint in &constraints {
+                for idx in &constraint.unknown {
+                    by_tile.entry(*idx).or_default().push(constraint);
+                }
+            }
+            for a in &constraints {
+                // the pairs are still many on large boards
+                if self.is_timed_out() {
+                    return false;
+                }
+                for b in &by_tile[&a.unknown[0]] {
+                    if a.unknown.len() >= b.unknown.len() {
+                        continue;
+                    }
+                    let Some(rest_bombs) = b.bombs.checked_sub(a.bombs) else {
+                        continue;
+                    };
+                    let Some(rest) = a.rest_of(b) else {
+                        continue;
+                    };
+                    if rest_bombs == 0 {
+                        safe.extend(rest);
+                    } else if rest_bombs == rest.len() {
+                        bombs.extend(rest);
+                    }
+                }
+            }
+        }
+
+        // whole board: remaining bombs count
+        if safe.is_empty() && bombs.is_empty() {
+            let unknown: Vec<usize> = self
+                .covered
+                .iter()
+                .filter(|idx| !self.flagged.contains(*idx))
+                .collect();
+            let flags = self.flagged.count();
+            let remaining = (self.tile_map.bomb_count() as usize).saturating_sub(flags);
+            if remaining == 0 {
+                safe.extend(unknown);
+            } else if remaining == unknown.len() {
+                bombs.extend(unknown);
+            }
+        }
+
+        for idx in &bombs {
+            self.flagged.set(*idx, true);
+        }
+        for idx in &safe {
+            let coordinates = self.coordinates(*idx);
+            self.uncover(coordinates);
+        }
+
+        !(safe.is_empty() && bombs.is_empty())
+    }
+
+    /// Covered tiles proven safe by the first deductions which find any, empty if the
+    /// next move needs a guess. The deduced bombs are flagged on the way
+    pub fn safe_tiles(&mut self) -> Vec<Coordinates> {
+        while !self.is_solved() && !self.is_timed_out() {
+            let covered = self.covered.clone();
+            if !self.step() {
+                break;
+            }
+            let safe: Vec<Coordinates> = covered
+                .iter()
+                .filter(|idx| !self.covered.contains(*idx))
+                .map(|idx| self.coordinates(idx))
+                .collect();
+            if !safe.is_empty() {
+                return safe;
+            }
+        }
+        Vec::new()
+    }
+
+    /// Deduces as long as possible or until the deadline. Returns `true` if the board is
+    /// solved without guessing
+    pub fn solve(&mut self) -> bool {
+        while !self.is_solved() && !self.is_timed_out() && self.step() {}
+        self.is_solved()
+    }
+
+    fn constraints(&self) -> Vec<Constraint> {
+        let mut constraints = Vec::new();
+        for idx in 0..self.covered.len() {
+            if self.covered.contains(idx) || self.tile_map.is_void(self.coordinates(idx)) {
+                continue;
+            }
+            let coordinates = self.coordinates(idx);
+            let Tile::BombNeighbour(count) = self.tile(coordinates) else {
+                continue;
+            };
+
+            let mut unknown = Vec::new();
+            let mut flags = 0;
+            for neighbour in self.neighbours(coordinates) {
+                if self.flagged.contains(neighbour) {
+                    flags += 1;
+                } else if self.covered.contains(neighbour) {
+                    unknown.push(neighbour);
+                }
+            }
+            if unknown.is_empty() {
+                continue;
+            }
+            unknown.sort_unstable();
+            constraints.push(Constraint {
+                unknown,
+                bombs: (count as usize).saturating_sub(flags),
+            });
+        }
+        constraints
+    }
+
+    fn neighbours(&self, coordinates: Coordinates) -> impl Iterator<Item = usize> + '_ {
+        self.tile_map
+            .safe_square_at(coordinates)
+            .filter_map(|c| self.index(c))
+    }
+
+    fn tile(&self, coordinates: Coordinates) -> Tile {
+        self.tile_map[coordinates]
+    }
+
+    fn index(&self, coordinates: Coordinates) -> Option<usize> {
+        self.tile_map.index_of(coordinates)
+    }
+
+    fn coordinates(&self, idx: usize) -> Coordinates {
+        self.tile_map.coordinates_of(idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile_map::GenerationError;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::time::Duration;
+
+    const fn c(x: u16, y: u16) -> Coordinates {
+        Coordinates { x, y }
+    }
+
+    #[test]
+    fn solves_a_no_guess_board() {
+        // . 1 1 1
+        // . 1 B 1
+        // . 1 1 1
+        // the left column leaves the middle one, whose counters only locate the
+        // bomb when compared with each other
+        let tile_map = TileMap::empty(4, 3).with_bombs(&[c(2, 1)]);
+        let mut solver = Solver::new(&tile_map);
+        assert!(solver.uncover(c(0, 0)));
+        assert!(solver.is_covered(c(2, 0)));
+        assert!(solver.solve());
+        assert!(solver.is_solved());
+        assert!(solver.is_covered(c(2, 1)));
+        assert!(tile_map.tiles().filter(|t| *t != c(2, 1)).all(|t| !solver.is_covered(t)));
+    }
+
+    #[test]
+    fn stops_on_a_50_50() {
+        // . 1 B
+        // . 1 1
+        // the covered column is only seen by counters of 1, the bomb may be on top or below
+        let tile_map = TileMap::empty(3, 2).with_bombs(&[c(2, 1)]);
+        let mut solver = Solver::new(&tile_map);
+        assert!(solver.uncover(c(0, 0)));
+        assert!(!solver.solve());
+        assert!(solver.is_covered(c(2, 0)));
+        assert!(solver.is_covered(c(2, 1)));
+        assert!(solver.safe_tiles().is_empty());
+    }
+
+    #[test]
+    fn stops_at_the_deadline() {
+        let tile_map = TileMap::empty(4, 3).with_bombs(&[c(2, 1)]);
+        let mut solver = Solver::new(&tile_map).with_deadline(Instant::now());
+        assert!(solver.uncover(c(0, 0)));
+        assert!(solver.is_timed_out());
+        assert!(!solver.solve());
+    }
+
+    #[test]
+    fn no_guess_generation_reports_a_50_50() {
+        // the start square leaves a single column, the bomb is in one of its 2 tiles
+        let mut tile_map = TileMap::empty(3, 2);
+        let result = tile_map.set_bombs_no_guess(
+            1,
+            &mut StdRng::seed_from_u64(1),
+            c(0, 0),
+            5,
+            Duration::from_secs(10),
+        );
+        assert_eq!(result, Err(GenerationError::AttemptsExhausted { attempts: 5 }));
+    }
+}
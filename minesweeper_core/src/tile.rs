@@ -1,13 +1,12 @@
-use std::fmt::format;
-
-use bevy::ecs::component::Component;
 #[cfg(feature = "debug")]
 use colored::Colorize;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug,Clone, Copy, PartialEq, Eq)]
 pub enum Tile {
     Bomb,
-    BombNeighbour(u8),
+    /// Bombs count of the neighbourhood, large custom neighbourhoods count beyond 255
+    BombNeighbour(u16),
     Empty,
 }
 
@@ -26,6 +25,8 @@ impl Tile {
                     1 => "1".cyan(),
                     2 => "2".green(),
                     3 => "3".yellow(),
+                    // keeps the one character wide columns
+                    10.. => "+".red(),
                     _ => bombs_count.to_string().red()
                 },
                 Tile::Empty => " ".black(),
@@ -0,0 +1,508 @@
+use crate::bitset::BitSet;
+use crate::coordinates::Coordinates;
+use crate::tile::Tile;
+use crate::mask::Mask;
+use crate::neighbourhood::Neighbourhood;
+use crate::solver::Solver;
+use crate::topology::Topology;
+
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Index;
+use std::time::Duration;
+
+use instant::Instant;
+
+use rand::Rng;
+
+/// Failure of the no-guess generation, the map keeps the last attempted layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenerationError {
+    /// No solvable layout found in the allowed attempts
+    AttemptsExhausted { attempts: u32 },
+    /// No solvable layout found in the allowed time
+    Timeout { attempts: u32 },
+    /// The start is out of the map or a void cell
+    InvalidStart(Coordinates),
+}
+
+impl Display for GenerationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerationError::AttemptsExhausted { attempts } => {
+                write!(f, "no solvable board found in {} attempts", attempts)
+            }
+            GenerationError::Timeout { attempts } => {
+                write!(f, "no solvable board found in time ({} attempts)", attempts)
+            }
+            GenerationError::InvalidStart(start) => write!(f, "{} is not a tile", start),
+        }
+    }
+}
+
+impl std::error::Error for GenerationError {}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "TileMapData"))]
+#[derive(Debug, Clone, Default)]
+pub struct TileMap {
+    bomb_count: u16,
+    height: u16,
+    width: u16,
+    /// Tiles row by row from the bottom, see [`TileMap::index_of`]
+    map: Vec<Tile>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    topology: Topology,
+    #[cfg_attr(feature = "serde", serde(default))]
+    wrapping: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    neighbourhood: Neighbourhood,
+    /// Indices of the cells of the rectangle which are not tiles
+    #[cfg_attr(feature = "serde", serde(default))]
+    void: BitSet,
+}
+
+/// Deserialized fields of a [`TileMap`], checked before use
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "TileMap")]
+struct TileMapData {
+    bomb_count: u16,
+    height: u16,
+    width: u16,
+    map: Vec<Tile>,
+    #[serde(default)]
+    topology: Topology,
+    #[serde(default)]
+    wrapping: bool,
+    #[serde(default)]
+    neighbourhood: Neighbourhood,
+    #[serde(default)]
+    void: BitSet,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TileMapData> for TileMap {
+    type Error = &'static str;
+
+    fn try_from(data: TileMapData) -> Result<Self, Self::Error> {
+        let len = data.width as usize * data.height as usize;
+        if data.map.len() != len {
+            return Err("the tiles don't match the map size");
+        }
+        // maps saved without void cells have no mask
+        let void = if data.void.is_empty() { BitSet::new(len) } else { data.void };
+        if void.len() != len {
+            return Err("the void cells don't match the map size");
+        }
+        let bombs = data.map.iter().filter(|tile| tile.is_bomb()).count();
+        if bombs != data.bomb_count as usize {
+            return Err("the bombs don't match the bombs count");
+        }
+        Ok(Self {
+            bomb_count: data.bomb_count,
+            height: data.height,
+            width: data.width,
+            map: data.map,
+            topology: data.topology,
+            wrapping: data.wrapping,
+            neighbourhood: data.neighbourhood,
+            void,
+        })
+    }
+}
+
+impl TileMap {
+    pub fn empty(width: u16, height: u16) -> Self {
+        Self::with_topology(width, height, Topology::Square)
+    }
+
+    /// Map without bombs whose tiles have the `topology` shape
+    pub fn with_topology(width: u16, height: u16, topology: Topology) -> Self {
+        let len = width as usize * height as usize;
+        Self {
+            bomb_count: 0,
+            height,
+            width,
+            map: vec![Tile::Empty; len],
+            topology,
+            wrapping: false,
+            neighbourhood: Neighbourhood::Adjacent,
+            void: BitSet::new(len),
+        }
+    }
+
+    /// Joins the opposite edges of the map: tiles of the first and last columns are
+    /// neighbours, so are tiles of the first and last rows. Hexagonal maps need an even
+    /// number of rows (pointy top) or columns (flat top) to wrap seamlessly
+    pub fn with_wrapping(mut self, wrapping: bool) -> Self {
+        self.wrapping = wrapping;
+        self
+    }
+
+    /// Shapes the map with the tiles of `mask`, void cells of the mask are neither tiles
+    /// nor neighbours. The mask is cropped to the map size
+    pub fn with_mask(mut self, mask: &Mask) -> Self {
+        self.void.clear();
+        for index in 0..self.map.len() {
+            if !mask.is_tile(self.coordinates_of(index)) {
+                self.void.set(index, true);
+            }
+        }
+        self
+    }
+
+    /// Index of `coordinates` in the row-major storage, `None` if out of bounds.
+    /// Indices range from 0 to `width * height` and are stable for the map lifetime
+    pub fn index_of(&self, coordinates: Coordinates) -> Option<usize> {
+        if !self.is_in_bounds(coordinates) {
+            return None;
+        }
+        Some(coordinates.y as usize * self.width as usize + coordinates.x as usize)
+    }
+
+    /// Coordinates of an index given by [`TileMap::index_of`]
+    pub fn coordinates_of(&self, index: usize) -> Coordinates {
+        let width = self.width.max(1) as usize;
+        Coordinates {
+            x: (index % width) as u16,
+            y: (index / width) as u16,
+        }
+    }
+
+    /// Tile at `coordinates`, `None` if out of bounds. Void cells are `Tile::Empty`
+    pub fn get(&self, coordinates: Coordinates) -> Option<Tile> {
+        self.index_of(coordinates).map(|index| self.map[index])
+    }
+
+    /// Number of cells of the rectangle, void cells included
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Counts the bombs of `neighbourhood` instead of the adjacent tiles
+    pub fn with_neighbourhood(mut self, neighbourhood: Neighbourhood) -> Self {
+        self.neighbourhood = neighbourhood;
+        self
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn console_output(&self) -> String {
+        let mut buffer = format!(
+            "Map: ({},{}) with {} bombs:\n",
+            self.width, self.height, self.bomb_count
+        );
+
+        let table_separator: String = (0..=(self.width + 1)).map(|_| '-').collect();
+        buffer = format!("{}{}\n", buffer, table_separator);
+
+        for y in (0..self.height).rev() {
+            buffer = format!("{}|", buffer);
+            for x in 0..self.width {
+                let coordinates = Coordinates { x, y };
+                if self.is_void(coordinates) {
+                    buffer = format!("{}.", buffer);
+                    continue;
+                }
+                buffer = format!("{}{}", buffer, self[coordinates].console_output())
+            }
+            buffer = format!("{}|\n", buffer);
+        }
+
+        format!("{}{}", buffer, table_separator)
+    }
+
+    /// Neighbours of `coordinates` in the map neighbourhood, each one once. They cross the
+    /// edges of a wrapping map, otherwise the ones out of bounds are skipped
+    pub fn safe_square_at(&self, coordinates: Coordinates) -> impl Iterator<Item = Coordinates> + '_ {
+        let (width, height, wrapping) = (self.width, self.height, self.wrapping);
+        // on narrow wrapping maps or with custom offsets several offsets lead to the
+        // same tile, or back to the tile itself
+        let mut seen = self.may_repeat_neighbours().then(Vec::new);
+        self.neighbourhood
+            .deltas(self.topology, coordinates)
+            .filter_map(move |delta| match wrapping {
+                true => Some(coordinates.wrapping_offset(delta, width, height)),
+                false => coordinates.checked_offset(delta, width, height),
+            })
+            .filter(move |c| *c != coordinates && !self.is_void(*c))
+            .filter(move |c| match &mut seen {
+                Some(seen) if seen.contains(c) => false,
+                Some(seen) => {
+                    seen.push(*c);
+                    true
+                }
+                None => true,
+            })
+    }
+
+    /// Can two offsets of the neighbourhood lead to the same tile
+    fn may_repeat_neighbours(&self) -> bool {
+        let span = 2 * self.neighbourhood.reach();
+        matches!(self.neighbourhood, Neighbourhood::Custom(_))
+            || (self.wrapping && (self.width <= span || self.height <= span))
+    }
+
+    pub fn is_bomb_at(&self, coordinates: Coordinates) -> bool {
+        self.get(coordinates).is_some_and(|tile| tile.is_bomb())
+    }
+
+    pub fn bomb_count_at(&self, coordinates: Coordinates) -> u16 {
+        if self.is_bomb_at(coordinates) {
+            return 0;
+        }
+
+        let res = self
+            .safe_square_at(coordinates)
+            .filter(|coord| self.is_bomb_at(*coord))
+            .count();
+
+        // distinct (i8, i8) offsets other than (0, 0) are at most u16::MAX
+        res as u16
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn is_wrapping(&self) -> bool {
+        self.wrapping
+    }
+
+    pub fn neighbourhood(&self) -> &Neighbourhood {
+        &self.neighbourhood
+    }
+
+    pub fn bomb_count(&self) -> u16 {
+        self.bomb_count
+    }
+
+    pub fn is_in_bounds(&self, coordinates: Coordinates) -> bool {
+        coordinates.x < self.width && coordinates.y < self.height
+    }
+
+    /// In bounds and not a void cell of the mask
+    pub fn is_tile(&self, coordinates: Coordinates) -> bool {
+        self
+            .index_of(coordinates)
+            .is_some_and(|index| !self.void.contains(index))
+    }
+
+    pub fn is_void(&self, coordinates: Coordinates) -> bool {
+        self
+            .index_of(coordinates)
+            .is_some_and(|index| self.void.contains(index))
+    }
+
+    /// Coordinates of all tiles, void cells excluded, row by row from the bottom
+    pub fn tiles(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        (0..self.map.len())
+            .filter(|index| !self.void.contains(*index))
+            .map(|index| self.coordinates_of(index))
+    }
+
+    /// Number of tiles, void cells excluded
+    pub fn tile_count(&self) -> usize {
+        self.map.len() - self.void.count()
+    }
+
+    /// Board size in tile units, see [`Topology::board_extent`]
+    pub fn extent(&self) -> (f32, f32) {
+        self.topology.board_extent(self.width, self.height)
+    }
+
+    /// Places `bomb_count` bombs using `rng`. The same rng state, size and bomb count
+    /// always yield the same map.
+    pub fn set_bombs<R: Rng + ?Sized>(&mut self, bomb_count: u16, rng: &mut R) {
+        self.set_bombs_excluding(bomb_count, rng, &[]);
+    }
+
+    /// Same as [`TileMap::set_bombs`], but never places a bomb on `safe_tiles`.
+    /// The bomb count is reduced if there are not enough free tiles.
+    pub fn set_bombs_excluding<R: Rng + ?Sized>(
+        &mut self,
+        bomb_count: u16,
+        rng: &mut R,
+        safe_tiles: &[Coordinates],
+    ) {
+        let safe_tiles: HashSet<Coordinates> = safe_tiles
+            .iter()
+            .copied()
+            .filter(|c| self.is_tile(*c))
+            .collect();
+        let free_tiles = self.tile_count() - safe_tiles.len();
+        let bomb_count = bomb_count.min(free_tiles.min(u16::MAX as usize) as u16);
+
+        self.bomb_count = bomb_count;
+        let mut remaining_bombs = bomb_count;
+
+        while remaining_bombs > 0 {
+            let row = rng.gen_range(0..self.height);
+            let column = rng.gen_range(0..self.width);
+            let coordinates = Coordinates { x: column, y: row };
+            if safe_tiles.contains(&coordinates) || self.is_void(coordinates) {
+                continue;
+            }
+            let index = row as usize * self.width as usize + column as usize;
+            if let Tile::Empty = self.map[index] {
+                self.map[index] = Tile::Bomb;
+                remaining_bombs -= 1;
+            }
+        }
+
+        self.set_counters();
+    }
+
+    /// Places bombs until the board can be solved from `start` without guessing.
+    /// `start` and its neighbours are free of bombs. Returns the number of attempts.
+    /// Fails without placing bombs if `start` is not a tile
+    pub fn set_bombs_no_guess<R: Rng + ?Sized>(
+        &mut self,
+        bomb_count: u16,
+        rng: &mut R,
+        start: Coordinates,
+        max_attempts: u32,
+        max_duration: Duration,
+    ) -> Result<u32, GenerationError> {
+        if !self.is_tile(start) {
+            return Err(GenerationError::InvalidStart(start));
+        }
+        let mut safe_tiles = vec![start];
+        safe_tiles.extend(self.safe_square_at(start));
+
+        let started_at = Instant::now();
+        let mut attempts = 0;
+        // at least one layout is always placed
+        loop {
+            self.clear();
+            self.set_bombs_excluding(bomb_count, rng, &safe_tiles);
+            attempts += 1;
+
+            let mut solver = Solver::new(self).with_deadline(started_at + max_duration);
+            if solver.uncover(start) && solver.solve() {
+                return Ok(attempts);
+            }
+            if solver.is_timed_out() {
+                return Err(GenerationError::Timeout { attempts });
+            }
+            if attempts >= max_attempts {
+                return Err(GenerationError::AttemptsExhausted { attempts });
+            }
+        }
+    }
+
+    /// Map with bombs on `bombs`, for known layouts in tests
+    #[cfg(test)]
+    pub(crate) fn with_bombs(mut self, bombs: &[Coordinates]) -> Self {
+        self.clear();
+        for coordinates in bombs {
+            if let Some(index) = self.index_of(*coordinates) {
+                self.map[index] = Tile::Bomb;
+                self.bomb_count += 1;
+            }
+        }
+        self.set_counters();
+        self
+    }
+
+    fn clear(&mut self) {
+        self.map.fill(Tile::Empty);
+        self.bomb_count = 0;
+    }
+
+    fn set_counters(&mut self) {
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let coords = Coordinates { y: row, x: col };
+
+                if self.is_bomb_at(coords) || self.is_void(coords) {
+                    continue;
+                };
+
+                let bomb_count = self.bomb_count_at(coords);
+                if bomb_count == 0 {
+                    continue;
+                }
+
+                let index = row as usize * self.width as usize + col as usize;
+                self.map[index] = Tile::BombNeighbour(bomb_count);
+            }
+        }
+    }
+}
+
+impl Index<Coordinates> for TileMap {
+    type Output = Tile;
+
+    /// Tile at `coordinates`, panics if out of bounds. See [`TileMap::get`]
+    fn index(&self, coordinates: Coordinates) -> &Self::Output {
+        let index = self
+            .index_of(coordinates)
+            .unwrap_or_else(|| panic!("{} is out of the map bounds", coordinates));
+        &self.map[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn bombs(tile_map: &TileMap) -> Vec<Coordinates> {
+        tile_map.tiles().filter(|c| tile_map.is_bomb_at(*c)).collect()
+    }
+
+    #[test]
+    fn same_seed_same_layout() {
+        let mut first = TileMap::empty(20, 15);
+        let mut second = TileMap::empty(20, 15);
+        first.set_bombs(40, &mut StdRng::seed_from_u64(42));
+        second.set_bombs(40, &mut StdRng::seed_from_u64(42));
+        assert_eq!(bombs(&first).len(), 40);
+        assert_eq!(bombs(&first), bombs(&second));
+        assert!(first.tiles().all(|c| first[c] == second[c]));
+
+        let mut other = TileMap::empty(20, 15);
+        other.set_bombs(40, &mut StdRng::seed_from_u64(43));
+        assert_ne!(bombs(&first), bombs(&other));
+    }
+
+    #[test]
+    fn excluded_square_stays_free() {
+        let mut tile_map = TileMap::empty(5, 5);
+        let square: Vec<Coordinates> = (1..=3)
+            .flat_map(|x| (1..=3).map(move |y| Coordinates { x, y }))
+            .collect();
+        // 16 tiles are left for the 20 bombs
+        tile_map.set_bombs_excluding(20, &mut StdRng::seed_from_u64(42), &square);
+        assert_eq!(tile_map.bomb_count(), 16);
+        assert_eq!(bombs(&tile_map).len(), 16);
+        assert!(square.iter().all(|c| !tile_map.is_bomb_at(*c)));
+    }
+
+    #[test]
+    fn same_seed_same_no_guess_layout() {
+        let start = Coordinates { x: 4, y: 4 };
+        let generate = || {
+            let mut tile_map = TileMap::empty(9, 9);
+            let attempts = tile_map
+                .set_bombs_no_guess(10, &mut StdRng::seed_from_u64(7), start, 1000, Duration::from_secs(10))
+                .unwrap();
+            (attempts, bombs(&tile_map))
+        };
+        assert_eq!(generate(), generate());
+    }
+}
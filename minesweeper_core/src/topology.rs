@@ -0,0 +1,253 @@
+use crate::coordinates::Coordinates;
+
+// Delta coordinates for all 8 square neighbors
+// [column, row]
+const SQUARE_COORDINATES: [(i8, i8); 8] = [
+    (-1, -1), // Bottom left
+    (0, -1),  // Bottom
+    (1, -1),  // Bottom right
+    (-1, 0),  // Left
+    (1, 0),   // Right
+    (-1, 1),  // Top Left
+    (0, 1),   // Top
+    (1, 1),   // Top Right
+];
+
+// Pointy top hexagons, odd rows are shifted half a tile to the right
+const POINTY_EVEN_ROW_COORDINATES: [(i8, i8); 6] = [(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)];
+const POINTY_ODD_ROW_COORDINATES: [(i8, i8); 6] = [(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)];
+
+// Flat top hexagons, odd columns are shifted half a tile up
+const FLAT_EVEN_COLUMN_COORDINATES: [(i8, i8); 6] = [(-1, -1), (1, -1), (0, -1), (0, 1), (-1, 0), (1, 0)];
+const FLAT_ODD_COLUMN_COORDINATES: [(i8, i8); 6] = [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, 1), (1, 1)];
+
+/// Step between two rows of pointy top hexagons, or two columns of flat top ones: 1.5 times
+/// the circumradius of 1/sqrt(3), i.e. sqrt(3)/2. Also sin 60° in `is_inside_tile`
+const HEX_ROW_SPACING: f32 = 0.866_025_4;
+
+/// Orientation of hexagonal tiles
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HexOrientation {
+    /// Tiles are in rows, a vertex on top
+    #[default]
+    PointyTop,
+    /// Tiles are in columns, a side on top
+    FlatTop,
+}
+
+/// Shape of the tiles: which tiles are neighbours and where they are drawn.
+///
+/// Positions are in tile units with the origin at the bottom left of the board and
+/// row 0 at the bottom. A tile unit is the side of a square tile, or the distance
+/// between two opposite sides of a hexagonal tile.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Topology {
+    /// Square tiles with 8 neighbours
+    #[default]
+    Square,
+    /// Hexagonal tiles with 6 neighbours, stored in offset coordinates
+    Hex(HexOrientation),
+}
+
+impl Topology {
+    /// Offsets of the neighbours of `coordinates`, hexagonal neighbours depend on the
+    /// parity of the row or column
+    pub fn neighbour_deltas(&self, coordinates: Coordinates) -> &'static [(i8, i8)] {
+        match self {
+            Topology::Square => &SQUARE_COORDINATES,
+            Topology::Hex(HexOrientation::PointyTop) if coordinates.y.is_multiple_of(2) => {
+                &POINTY_EVEN_ROW_COORDINATES
+            }
+            Topology::Hex(HexOrientation::PointyTop) => &POINTY_ODD_ROW_COORDINATES,
+            Topology::Hex(HexOrientation::FlatTop) if coordinates.x.is_multiple_of(2) => {
+                &FLAT_EVEN_COLUMN_COORDINATES
+            }
+            Topology::Hex(HexOrientation::FlatTop) => &FLAT_ODD_COLUMN_COORDINATES,
+        }
+    }
+
+    /// Center of a tile
+    pub fn tile_center(&self, coordinates: Coordinates) -> (f32, f32) {
+        self.center(coordinates.x as i32, coordinates.y as i32)
+    }
+
+    /// Size of a `width` x `height` board
+    pub fn board_extent(&self, width: u16, height: u16) -> (f32, f32) {
+        let (width, height) = (width as f32, height as f32);
+        match self {
+            Topology::Square => (width, height),
+            Topology::Hex(HexOrientation::PointyTop) => (
+                width + if height > 1.0 { 0.5 } else { 0.0 },
+                (height - 1.0).max(0.0) * HEX_ROW_SPACING + 2.0 / 3.0_f32.sqrt(),
+            ),
+            Topology::Hex(HexOrientation::FlatTop) => (
+                (width - 1.0).max(0.0) * HEX_ROW_SPACING + 2.0 / 3.0_f32.sqrt(),
+                height + if width > 1.0 { 0.5 } else { 0.0 },
+            ),
+        }
+    }
+
+    /// Size of the box around a tile: the side of a square, or the distances between the
+    /// opposite sides and the opposite vertices of a hexagon
+    pub fn tile_extent(&self) -> (f32, f32) {
+        match self {
+            Topology::Square => (1.0, 1.0),
+            Topology::Hex(HexOrientation::PointyTop) => (1.0, 2.0 / 3.0_f32.sqrt()),
+            Topology::Hex(HexOrientation::FlatTop) => (2.0 / 3.0_f32.sqrt(), 1.0),
+        }
+    }
+
+    /// Is `offset` from a tile center inside the tile shape whose opposite sides are
+    /// `2 * apothem` apart, e.g. the shape of a tile without its padding
+    pub fn is_inside_tile(&self, (x, y): (f32, f32), apothem: f32) -> bool {
+        let (x, y) = (x.abs(), y.abs());
+        match self {
+            Topology::Square => x <= apothem && y <= apothem,
+            // the other sides are at 60 degrees
+            Topology::Hex(HexOrientation::PointyTop) => {
+                x <= apothem && x * 0.5 + y * HEX_ROW_SPACING <= apothem
+            }
+            Topology::Hex(HexOrientation::FlatTop) => {
+                y <= apothem && y * 0.5 + x * HEX_ROW_SPACING <= apothem
+            }
+        }
+    }
+
+    /// Side of the square sprites drawn in a tile: its bomb, counter and flag
+    pub fn sprite_size(&self) -> f32 {
+        match self {
+            Topology::Square => 1.0,
+            // the square inscribed in the hexagon with an apothem of 0.5
+            Topology::Hex(_) => 3.0 - 3.0_f32.sqrt(),
+        }
+    }
+
+    /// Tile at `position`, which may be out of the board bounds. Returns `None` if
+    /// `position` is left of or below the first tiles
+    pub fn tile_at(&self, (x, y): (f32, f32)) -> Option<Coordinates> {
+        let (column, row) = match self {
+            Topology::Square => (x.floor() as i32, y.floor() as i32),
+            Topology::Hex(orientation) => {
+                // nearest center, the hexagons are the cells of the centers Voronoi diagram
+                let (guess_column, guess_row) = match orientation {
+                    HexOrientation::PointyTop => {
+                        let row = ((y - 1.0 / 3.0_f32.sqrt()) / HEX_ROW_SPACING).round() as i32;
+                        (x.floor() as i32, row)
+                    }
+                    HexOrientation::FlatTop => {
+                        let column = ((x - 1.0 / 3.0_f32.sqrt()) / HEX_ROW_SPACING).round() as i32;
+                        (column, y.floor() as i32)
+                    }
+                };
+                let distance = |(column, row): (i32, i32)| {
+                    let (cx, cy) = self.center(column, row);
+                    (cx - x).powi(2) + (cy - y).powi(2)
+                };
+                (-1..=1)
+                    .flat_map(|dy| (-1..=1).map(move |dx| (guess_column + dx, guess_row + dy)))
+                    .min_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+                    .unwrap_or((guess_column, guess_row))
+            }
+        };
+        if column < 0 || row < 0 || column > u16::MAX as i32 || row > u16::MAX as i32 {
+            return None;
+        }
+        Some(Coordinates {
+            x: column as u16,
+            y: row as u16,
+        })
+    }
+
+    fn center(&self, column: i32, row: i32) -> (f32, f32) {
+        let (column_f, row_f) = (column as f32, row as f32);
+        match self {
+            Topology::Square => (column_f + 0.5, row_f + 0.5),
+            Topology::Hex(HexOrientation::PointyTop) => (
+                column_f + 0.5 + if row.rem_euclid(2) == 1 { 0.5 } else { 0.0 },
+                row_f * HEX_ROW_SPACING + 1.0 / 3.0_f32.sqrt(),
+            ),
+            Topology::Hex(HexOrientation::FlatTop) => (
+                column_f * HEX_ROW_SPACING + 1.0 / 3.0_f32.sqrt(),
+                row_f + 0.5 + if column.rem_euclid(2) == 1 { 0.5 } else { 0.0 },
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile_map::TileMap;
+
+    const HEXES: [Topology; 2] = [
+        Topology::Hex(HexOrientation::PointyTop),
+        Topology::Hex(HexOrientation::FlatTop),
+    ];
+
+    fn neighbour_count(tile_map: &TileMap, x: u16, y: u16) -> usize {
+        tile_map.safe_square_at(Coordinates { x, y }).count()
+    }
+
+    #[test]
+    fn hex_neighbours_are_mutual() {
+        for topology in HEXES {
+            let tile_map = TileMap::with_topology(6, 6, topology);
+            for tile in tile_map.tiles() {
+                for neighbour in tile_map.safe_square_at(tile) {
+                    assert!(
+                        tile_map.safe_square_at(neighbour).any(|c| c == tile),
+                        "{:?}: {} is not a neighbour of {}",
+                        topology,
+                        tile,
+                        neighbour
+                    );
+                }
+            }
+            assert_eq!(neighbour_count(&tile_map, 2, 2), 6);
+            assert_eq!(neighbour_count(&tile_map, 3, 3), 6);
+        }
+    }
+
+    #[test]
+    fn wrapping_neighbour_counts() {
+        let square = TileMap::empty(5, 5);
+        assert_eq!(neighbour_count(&square, 0, 0), 3);
+        assert_eq!(neighbour_count(&square, 0, 2), 5);
+        let square = square.with_wrapping(true);
+        assert_eq!(neighbour_count(&square, 0, 0), 8);
+        assert_eq!(neighbour_count(&square, 4, 2), 8);
+
+        for topology in HEXES {
+            let hex = TileMap::with_topology(6, 6, topology).with_wrapping(true);
+            assert!(hex.tiles().all(|c| hex.safe_square_at(c).count() == 6), "{:?}", topology);
+        }
+
+        // the offsets on both sides lead to the same tiles
+        let narrow = TileMap::empty(2, 2).with_wrapping(true);
+        assert_eq!(neighbour_count(&narrow, 0, 0), 3);
+    }
+
+    #[test]
+    fn tiles_are_picked_at_their_center() {
+        for topology in [Topology::Square, HEXES[0], HEXES[1]] {
+            let tile_map = TileMap::with_topology(7, 5, topology);
+            for tile in tile_map.tiles() {
+                assert_eq!(topology.tile_at(topology.tile_center(tile)), Some(tile));
+            }
+        }
+    }
+
+    #[test]
+    fn hex_tiles_exclude_the_corners_of_their_box() {
+        let (pointy, flat) = (HEXES[0], HEXES[1]);
+        let (width, height) = pointy.tile_extent();
+        assert!(pointy.is_inside_tile((0.0, height / 2.0 - 0.01), 0.5));
+        assert!(!pointy.is_inside_tile((width / 2.0 - 0.01, height / 2.0 - 0.01), 0.5));
+        let (width, height) = flat.tile_extent();
+        assert!(flat.is_inside_tile((width / 2.0 - 0.01, 0.0), 0.5));
+        assert!(!flat.is_inside_tile((width / 2.0 - 0.01, height / 2.0 - 0.01), 0.5));
+        assert!(Topology::Square.is_inside_tile((0.49, 0.49), 0.5));
+    }
+}
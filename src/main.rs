@@ -1,28 +1,45 @@
 use bevy::log::LogPlugin;
-use bevy::log;
 use bevy::{prelude::*, window::WindowResolution};
 
-use bevy_inspector_egui::quick::ResourceInspectorPlugin;
 #[cfg(feature = "debug")]
-use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use bevy_inspector_egui::quick::{ResourceInspectorPlugin, WorldInspectorPlugin};
+#[cfg(feature = "debug")]
 use board_plugin::components::Coordinates;
-use board_plugin::resources::{BoardOptions, BoardSize, BoardAssets, SpriteMaterial};
-use board_plugin::resources::TileSize::Fixed;
-use board_plugin::BoardPlugin;
+use board_plugin::resources::{ActiveTheme, BoardOptions, BoardSize, BoardAssets, BoardTheme, InputAction};
+use board_plugin::resources::TileSize;
+use board_plugin::events::{LoadGameEvent, NoGuessFailedEvent, SaveGameEvent};
+use board_plugin::{ActionInput, BoardPlugin};
+use serde::{Deserialize, Serialize};
+
+/// Save file of the `InputAction::Save` and `InputAction::Load` actions, F5 and F9 by default
+const SAVE_FILE: &str = "savegame.ron";
+
+/// Themes switched with `InputAction::NextTheme`, the first one is used at start
+const THEME_FILES: [&str; 2] = ["themes/classic.theme.ron", "themes/dark.theme.ron"];
+
+/// Loaded themes and the index of the active one
+#[derive(Resource)]
+struct Themes {
+    handles: Vec<Handle<BoardTheme>>,
+    current: usize,
+}
 
 #[cfg_attr(feature = "debug", derive(Reflect))]
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, States)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, States, Serialize, Deserialize)]
 pub enum AppState {
-    NewGame,
-    #[default] InGame,
+    /// Waiting for the theme
+    #[default] NewGame,
+    InGame,
     Pause,
     EndGame
 }
 fn main() {
     let mut app = App::new();
-    let mut primary_window = Window::default();
-    primary_window.resolution = WindowResolution::new(850., 850.0);
-    primary_window.title = "Mine Sweeper!".to_string();
+    let primary_window = Window {
+        resolution: WindowResolution::new(850., 850.0),
+        title: "Mine Sweeper!".to_string(),
+        ..Default::default()
+    };
     // adapted from 0.8
     app.add_plugins(
         DefaultPlugins
@@ -38,11 +55,14 @@ fn main() {
     );
     // adapted from 0.8, 0.10 to 0.11
     app.add_systems(Startup, (camera_setup, board_setup));
+    app.add_systems(Update, (save_load_keys, switch_theme, switch_palette, warn_guessing));
+    app.add_systems(Update, start_when_themed.run_if(in_state(AppState::NewGame)));
 
     app.add_state::<AppState>();
     app.add_plugins(BoardPlugin{
         game_state: AppState::InGame,
         pause_state: AppState::Pause,
+        end_state: AppState::EndGame,
     });
 
 
@@ -63,33 +83,94 @@ fn main() {
 
 fn camera_setup(mut commands: Commands) {
     // adopted 0.7 to 0.8
-    commands.spawn(Camera2dBundle::default());
+    commands.spawn((Camera2dBundle::default(), board_plugin::components::BoardCamera::default()));
 }
 
 fn board_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.insert_resource(BoardAssets{
-        label: "Default".to_string(),
-        board_material: SpriteMaterial::color(Color::WHITE),
-        tile_material: SpriteMaterial::color(Color::DARK_GRAY),
-        covered_tile_material: SpriteMaterial::color(Color::GRAY),
-        bomb_counter_font: asset_server.load("fonts/pixeled.ttf"),
-        bomb_counter_colors: BoardAssets::default_colors(),
-        flag_material: SpriteMaterial::texture(&asset_server.load("sprites/flag.png")),
-        bomb_material: SpriteMaterial::texture(&asset_server.load("sprites/bomb.png")),
-        menu_font: asset_server.load("fonts/neuropol_x_rg.otf"),
-    });
+    let handles: Vec<Handle<BoardTheme>> =
+        THEME_FILES.iter().map(|path| asset_server.load(*path)).collect();
+    // the board plugin builds BoardAssets from the active theme
+    commands.insert_resource(ActiveTheme(handles[0].clone()));
+    commands.insert_resource(Themes { handles, current: 0 });
 
     commands.insert_resource(BoardOptions {
         map_size: BoardSize { columns: 4, rows: 4 },
+        shape: board_plugin::resources::BoardShape::Rectangle,
         bomb_count: 2,
         position: board_plugin::resources::BoardPosition::Centered { offset: Vec3::ZERO },            
         tile_padding: 3.0,
-        // different from tutorial due to WindowDescriptor is not available as a resource
-        tile_size: Fixed(35.0),
+        topology: board_plugin::minesweeper_core::Topology::Square,
+        wrapping: false,
+        neighbourhood: board_plugin::minesweeper_core::Neighbourhood::Adjacent,
+        // adaptive size is computed from the primary window and follows its resizing
+        tile_size: TileSize::Adaptive { min: 10.0, max: 50.0 },
         safe_start: true,
+        generation: board_plugin::resources::GenerationMode::NoGuess {
+            max_attempts: 1000,
+            max_duration: std::time::Duration::from_secs(1),
+        },
+        seed: None,
+        reveal_animation: Some(std::time::Duration::from_millis(30)),
+        cursor_movement: board_plugin::resources::CursorMovement::Clamp,
+        question_marks: true,
+        rng: None,
         game_state: AppState::InGame,
         pause_state: AppState::Pause,
+        end_state: AppState::EndGame,
     });
     
 
-}
\ No newline at end of file
+}
+
+/// Starts the game once the first theme is applied
+fn start_when_themed(board_assets: Option<Res<BoardAssets>>, mut next_state: ResMut<NextState<AppState>>) {
+    if board_assets.is_some() {
+        next_state.set(AppState::InGame);
+    }
+}
+
+/// Restyles the board with the next theme, T by default
+fn switch_theme(input: ActionInput, mut themes: ResMut<Themes>, mut active_theme: ResMut<ActiveTheme>) {
+    if input.just_released(InputAction::NextTheme) {
+        themes.current = (themes.current + 1) % themes.handles.len();
+        active_theme.0 = themes.handles[themes.current].clone();
+    }
+}
+
+/// Cycles the counter palettes, V by default, and toggles the counter glyphs, B by default
+fn switch_palette(input: ActionInput, board_assets: Option<ResMut<BoardAssets>>) {
+    let Some(mut board_assets) = board_assets else {
+        return;
+    };
+    if input.just_released(InputAction::NextPalette) {
+        board_assets.counter_palette = board_assets.counter_palette.next();
+        info!("Counter palette {:?}", board_assets.counter_palette);
+    }
+    if input.just_released(InputAction::ToggleGlyphs) {
+        board_assets.counter_glyphs = !board_assets.counter_glyphs;
+    }
+}
+
+/// Tells the player that the board may need guessing
+fn warn_guessing(mut no_guess_failed_evr: EventReader<NoGuessFailedEvent>) {
+    for event in no_guess_failed_evr.read() {
+        warn!("This board may need a guess: {}", event.error);
+    }
+}
+
+fn save_load_keys(
+    input: ActionInput,
+    mut save_game_ewr: EventWriter<SaveGameEvent>,
+    mut load_game_ewr: EventWriter<LoadGameEvent>,
+) {
+    if input.just_released(InputAction::Save) {
+        save_game_ewr.send(SaveGameEvent {
+            path: SAVE_FILE.into(),
+        });
+    }
+    if input.just_released(InputAction::Load) {
+        load_game_ewr.send(LoadGameEvent {
+            path: SAVE_FILE.into(),
+        });
+    }
+}